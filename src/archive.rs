@@ -0,0 +1,70 @@
+use crate::{ArchivedSpan, InvalidSpan, Span};
+
+/// Fails if the archived span is inconsistent, e.g. because it was read from
+/// a corrupted or stale cache, see [InvalidSpan]
+///
+/// ```
+/// # use span::*;
+/// let mut chars = &mut Chars::new("123\n456");
+/// let start = chars.start_token();
+/// for _ in chars.take(5) {}
+/// let span = chars.end_token(start);
+///
+/// let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&span).unwrap();
+/// let archived =
+///     rkyv::access::<ArchivedSpan, rkyv::rancor::Error>(&bytes).unwrap();
+/// assert_eq!(Span::try_from(archived), Ok(span));
+/// ```
+impl TryFrom<&ArchivedSpan> for Span {
+    type Error = InvalidSpan;
+
+    fn try_from(archived: &ArchivedSpan) -> Result<Self, Self::Error> {
+        let Ok(span) =
+            rkyv::deserialize::<Span, rkyv::rancor::Infallible>(archived);
+        // The relative placeholder of an unknown span doesn't survive being
+        // archived as 32 bits
+        if span.is_unknown() {
+            return Ok(Span::UNKNOWN);
+        }
+        span.validate()?;
+        Ok(span)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use rkyv::rancor::Error;
+
+    use super::*;
+    use crate::{AbsoluteSpan, LineAndColumn, Provenance, RelativeSpan};
+
+    #[test]
+    fn unknown_round_trip() {
+        let bytes = rkyv::to_bytes::<Error>(&Span::UNKNOWN).unwrap();
+        let archived = rkyv::access::<ArchivedSpan, Error>(&bytes).unwrap();
+        assert!(Span::try_from(archived).unwrap().is_unknown());
+    }
+
+    #[test]
+    fn invalid_span_is_rejected() {
+        let position = LineAndColumn { line: 1, column: 1 };
+        let backwards = Span {
+            absolute: Some(AbsoluteSpan {
+                start: 2,
+                end: 1,
+                start_byte: 2,
+                end_byte: 1,
+            }),
+            relative: RelativeSpan {
+                start: position,
+                end: position,
+            },
+            provenance: Provenance::NONE,
+        };
+        let bytes = rkyv::to_bytes::<Error>(&backwards).unwrap();
+        let archived = rkyv::access::<ArchivedSpan, Error>(&bytes).unwrap();
+        assert_eq!(Span::try_from(archived), Err(InvalidSpan::EndBeforeStart));
+    }
+}
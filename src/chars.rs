@@ -1,10 +1,44 @@
+use std::sync::Arc;
+
 use itertools::{Itertools as _, PeekNth, PeekingNext};
-use owned_chars::OwnedCharsExt;
 
-use crate::{AbsoluteSpan, LineAndColumn, RelativeSpan, Span};
+use crate::{AbsoluteSpan, LineAndColumn, Provenance, RelativeSpan, Span};
 
+mod balanced;
+mod builder;
+mod bulk;
 mod checkpoint;
-pub use self::checkpoint::Checkpoint;
+mod column_policy;
+mod comments;
+#[cfg(feature = "encoding_rs")]
+mod decode;
+mod feed;
+mod line_terminators;
+mod lookahead;
+mod lossy;
+mod recording;
+mod scan;
+mod shard;
+mod snapshot;
+mod stats;
+mod string;
+mod trace;
+mod trivia;
+pub use self::{
+    balanced::Balanced,
+    builder::{BuildError, CharsBuilder},
+    checkpoint::Checkpoint,
+    column_policy::ColumnPolicy,
+    line_terminators::LineTerminators,
+    lookahead::LookaheadExceeded,
+    lossy::InvalidUtf8,
+    scan::MalformedNumber,
+    snapshot::{ResumeError, Snapshot},
+    stats::Stats,
+    string::{EscapeError, StringLiteral, StringOptions},
+    trace::Trace,
+    trivia::WithTrivia,
+};
 
 /// TokenHandle
 #[expect(missing_copy_implementations, missing_debug_implementations)]
@@ -13,13 +47,82 @@ pub struct TokenHandle(Position);
 #[derive(Copy, Clone)]
 struct Position {
     loc: usize,
+    byte: usize,
     line: usize,
     col: usize,
+    /// The previous character was a `\r` that ended a line, so a `\n` now
+    /// doesn't end another one
+    after_cr: bool,
+}
+
+impl Position {
+    fn advance(
+        &mut self,
+        c: char,
+        bytes: usize,
+        policy: ColumnPolicy,
+        terminators: LineTerminators,
+    ) {
+        self.loc += 1;
+        self.byte += bytes;
+        let crlf = c == '\n' && self.after_cr;
+        self.after_cr = c == '\r' && terminators.carriage_return;
+        if crlf {
+            // The line was already ended by the '\r'
+        } else if terminators.ends_line(c) {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += policy.width(c, self.col);
+        }
+    }
+
+    fn span_to(self, end: Position) -> Span {
+        Span {
+            absolute: Some(AbsoluteSpan {
+                start: self.loc,
+                end: end.loc,
+                start_byte: self.byte,
+                end_byte: end.byte,
+            }),
+            relative: RelativeSpan {
+                start: LineAndColumn {
+                    line: self.line,
+                    column: self.col,
+                },
+                end: LineAndColumn {
+                    line: end.line,
+                    column: end.col,
+                },
+            },
+            provenance: Provenance::NONE,
+        }
+    }
+}
+
+/// Each character of the input along with the number of bytes it occupied
+type Decoded = Box<dyn Iterator<Item = (char, usize)> + Send>;
+
+/// Iterator over the characters of in-memory text between two byte offsets
+struct TextChars {
+    text: Arc<str>,
+    byte: usize,
+    end: usize,
+}
+
+impl Iterator for TextChars {
+    type Item = (char, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let c = self.text[self.byte..self.end].chars().next()?;
+        self.byte += c.len_utf8();
+        Some((c, c.len_utf8()))
+    }
 }
 
 /// Character iterator that automatically tracks line and column location
-/// The spans yielded by Chars uses 0 based indexing for absolute byte positions
-/// and 1 based indexing for relative indexing
+/// The spans yielded by Chars uses 0 based indexing for absolute character and
+/// byte positions and 1 based indexing for relative indexing
 ///
 /// The start_token and end_token methods are used to generate token spans
 /// pointing at ranges in the input
@@ -39,28 +142,199 @@ struct Position {
 /// assert_eq!(format!("{span1:#}"), "line 1 column 1 to column 4");
 /// assert_eq!(format!("{span2:#}"), "line 1 column 3 to column 7")
 /// ```
+///
+/// Chars is Send, so a partially consumed iterator can be moved to another
+/// thread or held across an `.await` on a multithreaded executor. It isn't
+/// Sync, use it from one thread at a time.
+///
+/// ```
+/// # use span::*;
+/// let mut chars = Chars::new("ab");
+/// assert_eq!(chars.next(), Some('a'));
+/// let handle = std::thread::spawn(move || chars.next());
+/// assert_eq!(handle.join().unwrap(), Some('b'));
+/// ```
 #[allow(missing_debug_implementations)]
 pub struct Chars {
-    it: PeekNth<Box<dyn Iterator<Item = char>>>,
+    /// Each character along with the number of bytes it occupied in the input
+    it: PeekNth<Decoded>,
+    /// The input, if it is held in memory as UTF-8. See [Chars::fork]
+    text: Option<Arc<str>>,
+    /// See [Chars::incremental]
+    feed: Option<feed::Feed>,
     current: Position,
+    /// Where the iterator started, so [Chars::stats] only counts what this
+    /// iterator consumed
+    start: Position,
+    column_policy: ColumnPolicy,
+    line_terminators: LineTerminators,
+    last_consumed: Option<char>,
+    /// Byte offset of the start of each line after the first that has been
+    /// reached, see [Chars::position_of]
+    line_starts: Vec<usize>,
+    /// See [Chars::recording]
+    recording: Option<recording::Recording>,
+    /// See [Chars::with_lookahead_limit]
+    lookahead_limit: Option<usize>,
+    lookahead_exceeded: Option<LookaheadExceeded>,
+    /// See [Chars::on_advance]. Behind a RefCell so it can be called from
+    /// start_token
+    hook: Option<trace::Hook>,
+    /// Only the counters that can't be derived from the position, see
+    /// [Chars::stats]
+    stats: Stats,
+    /// See [Chars::source_name]
+    source_name: Option<Arc<str>>,
 }
 
 impl Chars {
     /// Constructor
     #[must_use]
     pub fn new(str: impl Into<String>) -> Self {
-        let it: Box<dyn Iterator<Item = char>> =
-            Box::new(OwnedCharsExt::into_chars(str.into()));
+        Self::from_shared(str.into())
+    }
+
+    /// Iterate over text that is already shared, e.g. with an editor buffer.
+    /// An `Arc<str>` is used as is rather than copied, anything else that
+    /// converts to one (`&str`, `String`, `Cow<str>`...) is also accepted.
+    ///
+    /// ```
+    /// # use span::*;
+    /// # use std::sync::Arc;
+    /// let buffer: Arc<str> = Arc::from("fn main() {}");
+    /// let mut chars = Chars::from_shared(Arc::clone(&buffer));
+    /// // The buffer is shared rather than copied
+    /// assert!(Arc::strong_count(&buffer) > 1);
+    /// assert_eq!(chars.next(), Some('f'));
+    /// ```
+    #[must_use]
+    pub fn from_shared(text: impl Into<Arc<str>>) -> Self {
+        let text = text.into();
+        let mut chars = Self::from_decoded(
+            TextChars {
+                text: Arc::clone(&text),
+                byte: 0,
+                end: text.len(),
+            },
+            0,
+        );
+        chars.text = Some(text);
+        chars
+    }
+
+    /// Iterator over no input, see [Chars::reset]
+    #[must_use]
+    pub fn empty() -> Self {
+        Self::new("")
+    }
+
+    /// Start again from the beginning of `source`, keeping the column policy,
+    /// line terminators, [Chars::on_advance] hook, [Chars::recording] mode
+    /// and lookahead limit.
+    /// Everything else (position, statistics, last consumed character,
+    /// recorded text, lookahead violation, [Chars::incremental] input, source
+    /// name) is reset, so pooled iterators can be reused without being
+    /// reconfigured. Internal buffers keep their allocations, and an
+    /// `Arc<str>` source is shared rather than copied as in
+    /// [Chars::from_shared]
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::empty().with_column_policy(ColumnPolicy::Utf8);
+    /// assert_eq!(chars.next(), None);
+    /// chars.reset("£x");
+    /// let _ = chars.next();
+    /// let start = chars.start_token();
+    /// let _ = chars.next();
+    /// let span = chars.end_token(start);
+    /// assert_eq!(format!("{span:#}"), "line 1 column 3");
+    /// assert_eq!(chars.last_consumed(), Some('x'));
+    /// chars.reset("y");
+    /// assert_eq!(chars.last_consumed(), None);
+    /// assert_eq!(chars.stats(), Chars::new("y").stats());
+    /// ```
+    pub fn reset(&mut self, source: impl Into<Arc<str>>) {
+        let text = source.into();
+        // The peek buffer can't be handed a new iterator so it is replaced,
+        // everything else is reset in place
+        let it: Decoded = Box::new(TextChars {
+            text: Arc::clone(&text),
+            byte: 0,
+            end: text.len(),
+        });
+        self.it = itertools::peek_nth(it);
+        self.text = Some(text);
+        self.feed = None;
+        self.current = Position {
+            loc: 0,
+            byte: 0,
+            line: 1,
+            col: 1,
+            after_cr: false,
+        };
+        self.start = self.current;
+        self.last_consumed = None;
+        self.line_starts.clear();
+        if let Some(recording) = &mut self.recording {
+            recording.clear();
+        }
+        self.lookahead_exceeded = None;
+        self.stats = Stats::default();
+        self.source_name = None;
+    }
+
+    /// `it` yields each character along with the number of bytes it occupied
+    /// in the input, which begins `start_byte` bytes in
+    fn from_decoded(
+        it: impl Iterator<Item = (char, usize)> + Send + 'static,
+        start_byte: usize,
+    ) -> Self {
+        let it: Decoded = Box::new(it);
+        let start = Position {
+            loc: 0,
+            byte: start_byte,
+            line: 1,
+            col: 1,
+            after_cr: false,
+        };
         Self {
             it: itertools::peek_nth(it),
-            current: Position {
-                loc: 0,
-                line: 1,
-                col: 1,
-            },
+            text: None,
+            feed: None,
+            current: start,
+            start,
+            column_policy: ColumnPolicy::default(),
+            line_terminators: LineTerminators::default(),
+            last_consumed: None,
+            line_starts: Vec::new(),
+            recording: None,
+            lookahead_limit: None,
+            lookahead_exceeded: None,
+            hook: None,
+            stats: Stats::default(),
+            source_name: None,
         }
     }
 
+    /// Choose what a column means for the spans produced by this iterator,
+    /// see [ColumnPolicy]. Defaults to [ColumnPolicy::Chars]
+    #[must_use]
+    pub fn with_column_policy(mut self, column_policy: ColumnPolicy) -> Self {
+        self.column_policy = column_policy;
+        self
+    }
+
+    /// Choose which characters end a line, see [LineTerminators]. Defaults
+    /// to only `\n`
+    #[must_use]
+    pub fn with_line_terminators(
+        mut self,
+        line_terminators: LineTerminators,
+    ) -> Self {
+        self.line_terminators = line_terminators;
+        self
+    }
+
     /// Lookahead at the next item in the iterator without advancing. Peek
     /// always returns the same value until a call to next.
     ///
@@ -74,7 +348,132 @@ impl Chars {
     /// assert_eq!(chars.peek(), Some('2'));
     /// ```
     pub fn peek(&mut self) -> Option<char> {
-        self.it.peek().copied()
+        self.lookahead(0)
+    }
+
+    /// Lookahead at the rest of the current line without advancing, up to
+    /// but not including the next line terminator (see [LineTerminators]) or
+    /// the end of the input, e.g. to check whether the line is a directive
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("#pragma once\nint x;");
+    /// assert_eq!(chars.next(), Some('#'));
+    /// assert_eq!(chars.peek_line(), "pragma once");
+    /// assert_eq!(chars.next(), Some('p'));
+    /// for _ in (&mut chars).take(10) {}
+    /// assert_eq!(chars.peek_line(), "");
+    /// assert_eq!(chars.next(), Some('\n'));
+    /// assert_eq!(chars.peek_line(), "int x;");
+    /// ```
+    pub fn peek_line(&mut self) -> String {
+        self.peek_line_from(0)
+    }
+
+    /// Number of characters before the first one satisfying `pred`, without
+    /// advancing. If no character does this is the number of characters left.
+    /// `pred` sees the characters in order so it can track state, e.g. to
+    /// skip escaped quotes
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new(r#"a\"b" c"#);
+    /// let mut escaped = false;
+    /// let len = chars.peek_until(|c| {
+    ///     let end = c == '"' && !escaped;
+    ///     escaped = c == '\\' && !escaped;
+    ///     end
+    /// });
+    /// assert_eq!(len, 4);
+    /// assert_eq!(chars.peek_until(char::is_whitespace), 5);
+    /// assert_eq!(chars.peek_until(|c| c == '!'), 7);
+    /// assert_eq!(chars.next(), Some('a'));
+    /// ```
+    pub fn peek_until(&mut self, pred: impl FnMut(char) -> bool) -> usize {
+        self.peek_until_from(0, usize::MAX, pred)
+            .unwrap_or_else(|remaining| remaining)
+    }
+
+    /// [Chars::peek_until] looking at no more than `max` characters, for
+    /// staying within the limit set with [Chars::with_lookahead_limit].
+    /// Returns None if none of them satisfy `pred`, including when the input
+    /// ends first
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("abc\ndef");
+    /// assert_eq!(chars.peek_until_bounded(|c| c == '\n', 4), Some(3));
+    /// assert_eq!(chars.peek_until_bounded(|c| c == '\n', 3), None);
+    /// assert_eq!(chars.peek_until_bounded(|c| c == '!', 20), None);
+    /// ```
+    pub fn peek_until_bounded(
+        &mut self,
+        pred: impl FnMut(char) -> bool,
+        max: usize,
+    ) -> Option<usize> {
+        self.peek_until_from(0, max, pred).ok()
+    }
+
+    /// The character most recently consumed from the iterator, None at the
+    /// start of the input. Peeking and aborted checkpoints don't change it
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("a+");
+    /// assert_eq!(chars.last_consumed(), None);
+    /// assert_eq!(chars.next(), Some('a'));
+    /// assert_eq!(chars.peek(), Some('+'));
+    /// assert_eq!(chars.last_consumed(), Some('a'));
+    /// let _ = chars.next();
+    /// assert_eq!(chars.next(), None);
+    /// assert_eq!(chars.last_consumed(), Some('+'));
+    /// ```
+    #[must_use]
+    pub fn last_consumed(&self) -> Option<char> {
+        self.last_consumed
+    }
+
+    /// Line and column of a byte offset the iterator has already reached,
+    /// for positions found after the fact (e.g. by searching a token's text).
+    /// Columns follow the iterator's [ColumnPolicy].
+    ///
+    /// Returns None if the offset hasn't been reached yet or isn't on a
+    /// character boundary, or if the input isn't held in memory as UTF-8
+    /// (see [Chars::fork])
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("let x;\nlet yy = 1;");
+    /// for _ in chars.by_ref() {}
+    /// assert_eq!(
+    ///     chars.position_of(15),
+    ///     Some(LineAndColumn { line: 2, column: 9 })
+    /// );
+    /// assert_eq!(chars.position_of(99), None);
+    /// ```
+    #[must_use]
+    pub fn position_of(&self, offset: usize) -> Option<LineAndColumn> {
+        let text = self.text.as_deref()?;
+        if offset > self.current.byte || !text.is_char_boundary(offset) {
+            return None;
+        }
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = line.checked_sub(1).map_or(0, |i| self.line_starts[i]);
+        let mut line_text = &text[line_start..offset];
+        if self.line_terminators.carriage_return
+            && text[..line_start].ends_with('\r')
+        {
+            // The '\n' of a "\r\n" doesn't take up a column on the line the
+            // '\r' started
+            line_text = line_text.strip_prefix('\n').unwrap_or(line_text);
+        }
+        let column = line_text
+            .chars()
+            .fold(1, |column, c| column + self.column_policy.width(c, column));
+        Some(LineAndColumn {
+            line: line + 1,
+            column,
+        })
     }
 
     /// take_while except it only advances the iterator _after_ the test returns
@@ -98,6 +497,7 @@ impl Chars {
     /// Mark the beginning of a token
     #[must_use]
     pub fn start_token(&self) -> TokenHandle {
+        self.trace(|| Trace::StartToken(self.current.span_to(self.current)));
         TokenHandle(self.current)
     }
 
@@ -105,23 +505,19 @@ impl Chars {
     /// ending at the current location
     #[must_use]
     pub fn end_token(&mut self, TokenHandle(start): TokenHandle) -> Span {
-        let current = self.current;
-        Span {
-            absolute: Some(AbsoluteSpan {
-                start: start.loc,
-                end: current.loc,
-            }),
-            relative: RelativeSpan {
-                start: LineAndColumn {
-                    line: start.line,
-                    column: start.col,
-                },
-                end: LineAndColumn {
-                    line: current.line,
-                    column: current.col,
-                },
-            },
-        }
+        let span = start.span_to(self.current);
+        self.trace(|| Trace::EndToken(span));
+        span
+    }
+
+    /// [Chars::end_token] labelling the span with what created it, see
+    /// [Span::with_provenance]
+    pub fn end_token_from(
+        &mut self,
+        start: TokenHandle,
+        label: &'static str,
+    ) -> Span {
+        self.end_token(start).with_provenance(label)
     }
 
     /// Returns a wrapper iterator which can peek any number of items ahead
@@ -163,6 +559,71 @@ impl Chars {
         Checkpoint::new(self)
     }
 
+    /// Run `f` over a [Checkpoint] that is always aborted afterwards, for
+    /// decisions that need arbitrary lookahead but never consume anything
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("#include <x>");
+    /// let is_directive = chars.peek_with(|lookahead| {
+    ///     lookahead.next() == Some('#')
+    ///         && lookahead.take_while(|c| c.is_alphabetic()).count() > 0
+    /// });
+    /// assert!(is_directive);
+    /// assert_eq!(chars.next(), Some('#'));
+    /// ```
+    pub fn peek_with<T>(
+        &mut self,
+        f: impl FnOnce(&mut Checkpoint<'_>) -> T,
+    ) -> T {
+        let mut checkpoint = self.checkpoint();
+        let result = f(&mut checkpoint);
+        checkpoint.abort();
+        result
+    }
+
+    /// Independent cursor over the same input, starting at the current
+    /// position. The text is shared rather than copied so this is cheap. The
+    /// fork keeps the column policy but not the [Chars::on_advance] hook.
+    ///
+    /// Returns None if the input isn't held in memory as UTF-8 (e.g. when it
+    /// is being decoded from another encoding).
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("a+b");
+    /// assert_eq!(chars.next(), Some('a'));
+    /// let mut fork = chars.fork().unwrap();
+    /// let start = fork.start_token();
+    /// assert_eq!(fork.by_ref().collect::<String>(), "+b");
+    /// let span = fork.end_token(start);
+    /// assert_eq!(format!("{span:#}"), "line 1 column 2 to column 4");
+    /// assert_eq!(chars.next(), Some('+'));
+    /// ```
+    #[must_use]
+    pub fn fork(&self) -> Option<Chars> {
+        let text = self.text.clone()?;
+        let mut fork = Chars::from_decoded(
+            TextChars {
+                text: Arc::clone(&text),
+                byte: self.current.byte,
+                end: text.len(),
+            },
+            0,
+        );
+        fork.text = Some(text);
+        fork.current = self.current;
+        fork.start = self.start;
+        fork.column_policy = self.column_policy;
+        fork.line_terminators = self.line_terminators;
+        fork.last_consumed = self.last_consumed;
+        fork.line_starts.clone_from(&self.line_starts);
+        fork.lookahead_limit = self.lookahead_limit;
+        fork.stats = self.stats;
+        fork.source_name.clone_from(&self.source_name);
+        Some(fork)
+    }
+
     /// Remove any leading whitespace from the iterator (defined by
     /// [char::is_whitespace]) then *peek* the first non-whitespace character.
     ///
@@ -190,20 +651,177 @@ impl Chars {
         }
         None
     }
+
+    /// Consume `n` characters, equivalent to calling next `n` times. If the
+    /// end of the input is reached first returns the number of characters
+    /// that were consumed. Like [Chars::skip_line] the position is updated in
+    /// one step when the input is held in memory.
+    ///
+    /// ```
+    /// # use span::Chars;
+    /// let mut chars = Chars::new("123456");
+    /// assert_eq!(chars.advance_by(2), Ok(()));
+    /// assert_eq!(chars.next(), Some('3'));
+    /// assert_eq!(chars.advance_by(5), Err(3));
+    /// assert_eq!(chars.next(), None);
+    /// ```
+    ///
+    /// # Errors
+    /// If the input ends before `n` characters have been consumed
+    pub fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        let consumed = if let Some(text) = self.bulk_text() {
+            let rest = &text[self.current.byte..];
+            let (len, consumed) = match rest.char_indices().nth(n) {
+                Some((len, _)) => (len, n),
+                None => (rest.len(), rest.chars().count()),
+            };
+            self.advance_in_bulk(&text, len);
+            consumed
+        } else {
+            self.by_ref().take(n).count()
+        };
+        if consumed == n { Ok(()) } else { Err(consumed) }
+    }
+
+    /// Consume exactly `n` characters, returning them along with their span.
+    /// If fewer than `n` characters remain nothing is consumed and this
+    /// returns None.
+    ///
+    /// ```
+    /// # use span::Chars;
+    /// let mut chars = Chars::new(r"\u00e9\u12");
+    /// assert_eq!(chars.advance_by(2), Ok(()));
+    /// let (digits, span) = chars.take_span(4).unwrap();
+    /// assert_eq!(digits, "00e9");
+    /// assert_eq!(format!("{span:#}"), "line 1 column 3 to column 7");
+    /// assert_eq!(chars.advance_by(2), Ok(()));
+    /// assert_eq!(chars.take_span(4), None);
+    /// assert_eq!(chars.next(), Some('1'));
+    /// ```
+    pub fn take_span(&mut self, n: usize) -> Option<(String, Span)> {
+        if n > 0 {
+            let _ = self.lookahead(n - 1)?;
+        }
+        let start = self.start_token();
+        let text = self.by_ref().take(n).collect();
+        Some((text, self.end_token(start)))
+    }
+
+    /// Error recovery helper. Consume characters up to but *not* including the
+    /// next character in `sync` (or the end of the input) and return the span
+    /// of the skipped characters.
+    ///
+    /// ```
+    /// # use span::Chars;
+    /// let mut chars = Chars::new("let x = @#!; let y");
+    /// for _ in (&mut chars).take(8) {}
+    /// let skipped = chars.skip_to_sync(&[';', '}', '\n']);
+    /// assert_eq!(format!("{skipped:#}"), "line 1 column 9 to column 12");
+    /// assert_eq!(chars.next(), Some(';'));
+    ///
+    /// // If no sync character is found the rest of the input is skipped
+    /// let skipped = chars.skip_to_sync(&['}']);
+    /// assert_eq!(format!("{skipped:#}"), "line 1 column 13 to column 19");
+    /// assert_eq!(chars.next(), None);
+    /// ```
+    pub fn skip_to_sync(&mut self, sync: &[char]) -> Span {
+        let start = self.start_token();
+        for _ in self.peek_while(|c| !sync.contains(&c)) {}
+        self.end_token(start)
+    }
+
+    /// As [Chars::skip_to_sync] but the synchronization set is made up of
+    /// strings. Stops at the first position where the head of the iterator
+    /// matches any of the strings in `sync`.
+    ///
+    /// ```
+    /// # use span::Chars;
+    /// let mut chars = Chars::new("garbage */ end");
+    /// let skipped = chars.skip_to_sync_str(&["*/", "\n"]);
+    /// assert_eq!(format!("{skipped:#}"), "line 1 column 1 to column 9");
+    /// assert_eq!(chars.next(), Some('*'));
+    /// ```
+    pub fn skip_to_sync_str(&mut self, sync: &[&str]) -> Span {
+        let start = self.start_token();
+        while self.peek().is_some()
+            && !sync.iter().any(|s| self.head_matches(s))
+        {
+            let _ = self.next();
+        }
+        self.end_token(start)
+    }
+
+    /// Non-destructive check that the head of the iterator matches `s`
+    fn head_matches(&mut self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.lookahead(i) == Some(c))
+    }
+
+    /// Characters from `n` places ahead of the head of the iterator up to the
+    /// next line terminator
+    fn peek_line_from(&mut self, n: usize) -> String {
+        let terminators = self.line_terminators;
+        (n..)
+            .map_while(|i| self.lookahead(i))
+            .take_while(|&c| !terminators.ends_line(c))
+            .collect()
+    }
+
+    /// Distance from `n` places ahead of the head of the iterator to the first
+    /// character satisfying `pred`, looking at no more than `max` characters.
+    /// Err holds the number of characters looked at if none did
+    fn peek_until_from(
+        &mut self,
+        n: usize,
+        max: usize,
+        mut pred: impl FnMut(char) -> bool,
+    ) -> Result<usize, usize> {
+        for i in 0..max {
+            match self.lookahead(n + i) {
+                Some(c) if pred(c) => return Ok(i),
+                Some(_) => {}
+                None => return Err(i),
+            }
+        }
+        Err(max)
+    }
+
+    /// Peek at the character `n` places ahead of the head of the iterator,
+    /// all lookahead goes through here so it can be counted and limited
+    fn lookahead(&mut self, n: usize) -> Option<char> {
+        self.stats.peak_lookahead = self.stats.peak_lookahead.max(n + 1);
+        self.check_lookahead(n + 1);
+        self.it.peek_nth(n).map(|&(c, _)| c)
+    }
+}
+
+impl Default for Chars {
+    fn default() -> Self {
+        Self::empty()
+    }
 }
 
 impl Iterator for Chars {
     type Item = char;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let next = self.it.next()?;
-        self.current.loc += 1;
-        if next == '\n' {
-            self.current.line += 1;
-            self.current.col = 1;
-        } else {
-            self.current.col += 1;
+        let (next, bytes) = self.it.next()?;
+        let before = self.current;
+        self.current.advance(
+            next,
+            bytes,
+            self.column_policy,
+            self.line_terminators,
+        );
+        self.trace(|| Trace::Consumed(next, before.span_to(self.current)));
+        if self.current.line != before.line {
+            self.line_starts.push(self.current.byte);
+        }
+        if let Some(recording) = &mut self.recording {
+            recording.push(next);
         }
+        self.last_consumed = Some(next);
         Some(next)
     }
 }
@@ -230,6 +848,29 @@ impl PeekingNext for Chars {
 mod test {
     use super::*;
 
+    #[test]
+    fn peek_line_stops_at_configured_terminators() {
+        let mut chars = Chars::new("a\u{2028}b\rc")
+            .with_line_terminators(LineTerminators::UNICODE);
+        assert_eq!(chars.peek_line(), "a");
+        let _ = chars.advance_by(2);
+        assert_eq!(chars.checkpoint().peek_line(), "b");
+        assert_eq!(Chars::new("a\u{2028}b").peek_line(), "a\u{2028}b");
+    }
+
+    #[test]
+    fn reset_keeps_line_starts_allocation() {
+        let mut chars = Chars::new("a\nb\nc\n");
+        for _ in chars.by_ref() {}
+        let capacity = chars.line_starts.capacity();
+        assert!(capacity >= 3);
+        chars.reset("x\ny");
+        assert!(chars.line_starts.is_empty());
+        assert_eq!(chars.line_starts.capacity(), capacity);
+        assert_eq!(chars.by_ref().collect::<String>(), "x\ny");
+        assert_eq!(chars.line_starts, [2]);
+    }
+
     #[test]
     fn checkpoint_tracks_spans_correctly() {
         let mut chars = Chars::new("123456");
@@ -251,4 +892,78 @@ mod test {
         let span = chars.end_token(start);
         assert_eq!(format!("{span:#}"), "line 1 column 1 to column 4");
     }
+
+    #[test]
+    fn advance_by_tracks_spans_correctly() {
+        let mut chars = Chars::new("12\n£56");
+        let start = chars.start_token();
+        assert_eq!(chars.advance_by(4), Ok(()));
+        let span = chars.end_token(start);
+        assert_eq!(format!("{span:#}"), "line 1 column 1 to line 2 column 2");
+        assert_eq!(span.len_bytes(), Some(5));
+    }
+
+    #[test]
+    fn fork_ignores_lookahead() {
+        let mut chars = Chars::new("£12");
+        let _ = chars.next();
+        let mut checkpoint = chars.checkpoint();
+        assert!(checkpoint.head_matches("12"));
+        checkpoint.abort();
+        let mut fork = chars.fork().unwrap();
+        let start = fork.start_token();
+        assert_eq!(fork.next(), Some('1'));
+        let span = fork.end_token(start);
+        assert_eq!(span.start_byte(), Some(2));
+        assert_eq!(chars.collect::<String>(), "12");
+    }
+
+    #[test]
+    fn position_of_uses_column_policy() {
+        let mut chars =
+            Chars::new("a\n\t😀b").with_column_policy(ColumnPolicy::Utf16);
+        assert_eq!(chars.advance_by(4), Ok(()));
+        let position = |line, column| Some(LineAndColumn { line, column });
+        assert_eq!(chars.position_of(0), position(1, 1));
+        assert_eq!(chars.position_of(2), position(2, 1));
+        assert_eq!(chars.position_of(7), position(2, 4));
+        assert_eq!(chars.position_of(5), None);
+        assert_eq!(chars.position_of(8), None);
+    }
+
+    #[test]
+    fn position_of_uses_line_terminators() {
+        let mut chars = Chars::new("a\r\r\nb\u{2029}c")
+            .with_line_terminators(LineTerminators::UNICODE);
+        for _ in chars.by_ref() {}
+        let position = |line, column| Some(LineAndColumn { line, column });
+        assert_eq!(chars.position_of(2), position(2, 1));
+        assert_eq!(chars.position_of(3), position(3, 1));
+        assert_eq!(chars.position_of(4), position(3, 1));
+        assert_eq!(chars.position_of(5), position(3, 2));
+        assert_eq!(chars.position_of(8), position(4, 1));
+    }
+
+    #[test]
+    fn last_consumed_follows_checkpoints() {
+        let mut chars = Chars::new("123");
+        let mut checkpoint = chars.checkpoint();
+        assert_eq!(checkpoint.next(), Some('1'));
+        checkpoint.abort();
+        assert_eq!(chars.last_consumed(), None);
+        let mut checkpoint = chars.checkpoint();
+        assert_eq!(checkpoint.nth(1), Some('2'));
+        checkpoint.commit();
+        assert_eq!(chars.last_consumed(), Some('2'));
+        assert_eq!(chars.advance_by(1), Ok(()));
+        assert_eq!(chars.last_consumed(), Some('3'));
+    }
+
+    #[test]
+    fn skip_to_sync_tracks_lines() {
+        let mut chars = Chars::new("ab\ncd;");
+        let span = chars.skip_to_sync(&[';']);
+        assert_eq!(format!("{span:#}"), "line 1 column 1 to line 2 column 3");
+        assert_eq!(chars.next(), Some(';'));
+    }
 }
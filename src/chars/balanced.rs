@@ -0,0 +1,131 @@
+use super::{Chars, StringOptions};
+use crate::Span;
+
+/// Delimited text scanned by [Chars::scan_balanced]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Balanced {
+    /// The text between the outermost delimiters, exactly as written
+    pub text: String,
+    /// Span of `text`
+    pub inner: Span,
+    /// Span including the outermost delimiters
+    pub outer: Span,
+}
+
+impl Chars {
+    /// Consume from `open` up to the matching `close`, skipping over nested
+    /// pairs of delimiters and over string literals described by `strings`
+    /// (delimiters inside a string don't count). Returns None without
+    /// consuming anything if the next character isn't `open`.
+    ///
+    /// If the input ends before the matching `close` the span of everything
+    /// consumed is returned as the error.
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new(r#"(a (b) ")") c"#);
+    /// let balanced = chars
+    ///     .scan_balanced('(', ')', &[StringOptions::default()])
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(balanced.text, r#"a (b) ")""#);
+    /// assert_eq!(format!("{:#}", balanced.inner), "line 1 column 2 to column 11");
+    /// assert_eq!(format!("{:#}", balanced.outer), "line 1 column 1 to column 12");
+    /// assert_eq!(chars.next(), Some(' '));
+    ///
+    /// let mut chars = Chars::new("[[x]");
+    /// let missing = chars.scan_balanced('[', ']', &[]).unwrap();
+    /// assert_eq!(
+    ///     missing.map_err(|span| format!("{span:#}")),
+    ///     Err(String::from("line 1 column 1 to column 5"))
+    /// );
+    /// ```
+    pub fn scan_balanced(
+        &mut self,
+        open: char,
+        close: char,
+        strings: &[StringOptions],
+    ) -> Option<Result<Balanced, Span>> {
+        if self.peek() != Some(open) {
+            return None;
+        }
+        let outer = self.start_token();
+        let _ = self.next();
+        let inner = self.start_token();
+        let mut text = String::new();
+        let mut depth = 1;
+        while let Some(c) = self.peek() {
+            if c == close && depth == 1 {
+                let inner = self.end_token(inner);
+                let _ = self.next();
+                return Some(Ok(Balanced {
+                    text,
+                    inner,
+                    outer: self.end_token(outer),
+                }));
+            }
+            let _ = self.next();
+            text.push(c);
+            if c == close {
+                depth -= 1;
+            } else if c == open {
+                depth += 1;
+            } else if let Some(string) = strings.iter().find(|s| s.quote == c) {
+                self.skip_raw_string(*string, &mut text);
+            }
+        }
+        Some(Err(self.end_token(outer)))
+    }
+
+    /// Consume the rest of a string literal whose opening quote has already
+    /// been consumed, copying it into `text` without decoding escapes
+    fn skip_raw_string(&mut self, options: StringOptions, text: &mut String) {
+        while let Some(c) = self.peek() {
+            if self.line_terminators.ends_line(c) && !options.multiline {
+                return;
+            }
+            let _ = self.next();
+            text.push(c);
+            if c == options.quote {
+                return;
+            }
+            if c == options.escape {
+                text.extend(self.next());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::LineTerminators;
+
+    #[test]
+    fn escaped_quotes_and_unterminated_strings() {
+        let strings = [
+            StringOptions::default(),
+            StringOptions {
+                quote: '\'',
+                ..StringOptions::default()
+            },
+        ];
+        let mut chars = Chars::new("{\"}\\\"}\" '}\n}");
+        let balanced = chars.scan_balanced('{', '}', &strings).unwrap();
+        assert_eq!(balanced.unwrap().text, "\"}\\\"}\" '}\n");
+        assert_eq!(chars.next(), None);
+    }
+
+    #[test]
+    fn strings_end_at_unicode_line_terminators() {
+        let mut chars = Chars::new("(\"a\u{2028})")
+            .with_line_terminators(LineTerminators::UNICODE);
+        let balanced = chars
+            .scan_balanced('(', ')', &[StringOptions::default()])
+            .unwrap();
+        assert_eq!(balanced.unwrap().text, "\"a\u{2028}");
+    }
+}
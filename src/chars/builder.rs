@@ -0,0 +1,335 @@
+use std::{fmt, sync::Arc};
+
+use super::{Chars, ColumnPolicy, LineTerminators, TextChars};
+use crate::LineAndColumn;
+
+/// Settings passed to [CharsBuilder] that contradict each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuildError {
+    /// [CharsBuilder::tab_width] was combined with a different
+    /// [CharsBuilder::column_policy]
+    ConflictingColumnPolicy {
+        /// The requested tab width
+        tab_width: usize,
+        /// The requested column policy
+        column_policy: ColumnPolicy,
+    },
+    /// [CharsBuilder::crlf] disagrees with the `carriage_return` setting of
+    /// [CharsBuilder::line_terminators]
+    ConflictingLineTerminators,
+    /// [CharsBuilder::initial_position] has a line or column of 0, both are
+    /// 1 indexed
+    InvalidPosition(LineAndColumn),
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::ConflictingColumnPolicy {
+                tab_width,
+                column_policy,
+            } => write!(
+                f,
+                "tab width {tab_width} conflicts with column policy \
+                 {column_policy:?}"
+            ),
+            BuildError::ConflictingLineTerminators => write!(
+                f,
+                "crlf setting conflicts with the chosen line terminators"
+            ),
+            BuildError::InvalidPosition(LineAndColumn { line, column }) => {
+                write!(
+                    f,
+                    "initial position line {line} column {column} is not 1 \
+                     indexed"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Configures a [Chars] in one place, see [Chars::builder]. Settings left
+/// alone get the same defaults as [Chars::new]
+///
+/// ```
+/// # use span::*;
+/// let mut chars = Chars::builder("\u{feff}\tx\r\ny")
+///     .tab_width(4)
+///     .crlf(true)
+///     .strip_bom(true)
+///     .source_name("main.x")
+///     .build()?;
+/// assert_eq!(chars.source_name(), Some("main.x"));
+/// assert_eq!(chars.next(), Some('\t'));
+/// let start = chars.start_token();
+/// assert_eq!(chars.next(), Some('x'));
+/// let span = chars.end_token(start);
+/// assert_eq!(
+///     span.with_source_name("main.x").to_string(),
+///     "main.x: line 1 column 5"
+/// );
+///
+/// let conflict = Chars::builder("")
+///     .tab_width(4)
+///     .column_policy(ColumnPolicy::Utf16)
+///     .build();
+/// assert_eq!(
+///     conflict.err().map(|e| e.to_string()).as_deref(),
+///     Some("tab width 4 conflicts with column policy Utf16")
+/// );
+/// # Ok::<(), BuildError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct CharsBuilder {
+    text: Arc<str>,
+    tab_width: Option<usize>,
+    column_policy: Option<ColumnPolicy>,
+    crlf: Option<bool>,
+    line_terminators: Option<LineTerminators>,
+    source_name: Option<Arc<str>>,
+    initial_position: Option<LineAndColumn>,
+    strip_bom: bool,
+    lookahead_limit: Option<usize>,
+}
+
+impl CharsBuilder {
+    /// Measure columns as they would appear in a terminal with tab stops
+    /// every `tab_width` columns, i.e. [ColumnPolicy::Visual]
+    #[must_use]
+    pub fn tab_width(self, tab_width: usize) -> Self {
+        Self {
+            tab_width: Some(tab_width),
+            ..self
+        }
+    }
+
+    /// See [Chars::with_column_policy]
+    #[must_use]
+    pub fn column_policy(self, column_policy: ColumnPolicy) -> Self {
+        Self {
+            column_policy: Some(column_policy),
+            ..self
+        }
+    }
+
+    /// Whether `\r` and `\r\n` end a line as well as `\n`, i.e. the
+    /// `carriage_return` setting of [LineTerminators]
+    #[must_use]
+    pub fn crlf(self, crlf: bool) -> Self {
+        Self {
+            crlf: Some(crlf),
+            ..self
+        }
+    }
+
+    /// See [Chars::with_line_terminators]
+    #[must_use]
+    pub fn line_terminators(self, line_terminators: LineTerminators) -> Self {
+        Self {
+            line_terminators: Some(line_terminators),
+            ..self
+        }
+    }
+
+    /// Name of the source for messages, see [Chars::source_name]
+    #[must_use]
+    pub fn source_name(self, name: impl Into<Arc<str>>) -> Self {
+        Self {
+            source_name: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// Line and column the text starts at, for text taken from the middle of
+    /// a larger document (e.g. a code block in Markdown) so spans point into
+    /// the document. Byte and character offsets still start from 0. As with
+    /// [Chars::split_at_lines] the iterator only covers part of the document
+    /// so [Chars::fork] and [Chars::position_of] aren't available
+    #[must_use]
+    pub fn initial_position(self, position: LineAndColumn) -> Self {
+        Self {
+            initial_position: Some(position),
+            ..self
+        }
+    }
+
+    /// Skip a byte order mark at the start of the text. As with
+    /// [Chars::from_bytes] spans begin after it but byte offsets still index
+    /// the original text
+    #[must_use]
+    pub fn strip_bom(self, strip_bom: bool) -> Self {
+        Self { strip_bom, ..self }
+    }
+
+    /// See [Chars::with_lookahead_limit]
+    #[must_use]
+    pub fn lookahead_limit(self, limit: usize) -> Self {
+        Self {
+            lookahead_limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Create the iterator
+    ///
+    /// # Errors
+    /// If settings contradict each other, see [BuildError]
+    pub fn build(self) -> Result<Chars, BuildError> {
+        let column_policy = match (self.tab_width, self.column_policy) {
+            (Some(tab_width), Some(column_policy))
+                if column_policy != ColumnPolicy::Visual { tab_width } =>
+            {
+                return Err(BuildError::ConflictingColumnPolicy {
+                    tab_width,
+                    column_policy,
+                });
+            }
+            (Some(tab_width), _) => ColumnPolicy::Visual { tab_width },
+            (None, column_policy) => column_policy.unwrap_or_default(),
+        };
+        let line_terminators = match (self.crlf, self.line_terminators) {
+            (Some(crlf), Some(line_terminators))
+                if crlf != line_terminators.carriage_return =>
+            {
+                return Err(BuildError::ConflictingLineTerminators);
+            }
+            (crlf, line_terminators) => {
+                let line_terminators = line_terminators.unwrap_or_default();
+                LineTerminators {
+                    carriage_return: crlf
+                        .unwrap_or(line_terminators.carriage_return),
+                    ..line_terminators
+                }
+            }
+        };
+        if let Some(position) = self.initial_position
+            && (position.line == 0 || position.column == 0)
+        {
+            return Err(BuildError::InvalidPosition(position));
+        }
+
+        let bom = if self.strip_bom && self.text.starts_with('\u{feff}') {
+            '\u{feff}'.len_utf8()
+        } else {
+            0
+        };
+        let mut chars = Chars::from_decoded(
+            TextChars {
+                text: Arc::clone(&self.text),
+                byte: bom,
+                end: self.text.len(),
+            },
+            bom,
+        );
+        match self.initial_position {
+            Some(LineAndColumn { line, column }) => {
+                chars.current.line = line;
+                chars.current.col = column;
+                chars.start = chars.current;
+            }
+            None if bom == 0 => chars.text = Some(self.text),
+            // Keeping the text would let position_of count the BOM
+            None => {}
+        }
+        chars.column_policy = column_policy;
+        chars.line_terminators = line_terminators;
+        chars.source_name = self.source_name;
+        chars.lookahead_limit = self.lookahead_limit;
+        Ok(chars)
+    }
+}
+
+impl Chars {
+    /// Configure an iterator over `text` with a [CharsBuilder], for when
+    /// there are too many settings for the `with_*` methods to be readable
+    #[must_use]
+    pub fn builder(text: impl Into<Arc<str>>) -> CharsBuilder {
+        CharsBuilder {
+            text: text.into(),
+            tab_width: None,
+            column_policy: None,
+            crlf: None,
+            line_terminators: None,
+            source_name: None,
+            initial_position: None,
+            strip_bom: false,
+            lookahead_limit: None,
+        }
+    }
+
+    /// Name of the source set with [CharsBuilder::source_name], e.g. for
+    /// [Span::with_source_name](crate::Span::with_source_name)
+    #[must_use]
+    pub fn source_name(&self) -> Option<&str> {
+        self.source_name.as_deref()
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(
+        Chars::builder("")
+            .crlf(false)
+            .line_terminators(LineTerminators::UNICODE)
+    )]
+    #[case(Chars::builder("").crlf(true).line_terminators(LineTerminators::LF))]
+    #[case(
+        Chars::builder("")
+            .tab_width(4)
+            .column_policy(ColumnPolicy::Visual { tab_width: 8 })
+    )]
+    #[case(
+        Chars::builder("")
+            .initial_position(LineAndColumn { line: 0, column: 1 })
+    )]
+    fn conflicts(#[case] builder: CharsBuilder) {
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn initial_position() {
+        let mut chars = Chars::builder("a\nb")
+            .initial_position(LineAndColumn { line: 3, column: 5 })
+            .build()
+            .unwrap();
+        assert_eq!(chars.next(), Some('a'));
+        let span = chars.end_token(chars.start_token());
+        assert_eq!(span.to_string(), "line 3 column 6");
+        assert!(chars.fork().is_none());
+        let _ = chars.next();
+        let start = chars.start_token();
+        let _ = chars.next();
+        assert_eq!(chars.end_token(start).to_string(), "line 4 column 1");
+    }
+
+    #[test]
+    fn line_terminators_without_crlf() {
+        let mut chars = Chars::builder("a\rb")
+            .line_terminators(LineTerminators::UNICODE)
+            .build()
+            .unwrap();
+        let _ = chars.by_ref().take(2).count();
+        let span = chars.end_token(chars.start_token());
+        assert_eq!(span.to_string(), "line 2 column 1");
+    }
+
+    #[test]
+    fn defaults_match_new() {
+        let mut built = Chars::builder("x\r\ny").build().unwrap();
+        let mut new = Chars::new("x\r\ny");
+        for _ in built.by_ref() {}
+        for _ in new.by_ref() {}
+        assert_eq!(built.final_position(), new.final_position());
+        assert_eq!(built.source_name(), None);
+    }
+}
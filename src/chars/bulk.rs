@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use super::{Chars, LineTerminators, Position, TextChars};
+use crate::{LineAndColumn, Span};
+
+impl Chars {
+    /// Consume the rest of the input, returning its text and span, e.g. to
+    /// report unexpected trailing content. Like [Chars::skip_line] this is
+    /// done in bulk when the input is held in memory
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("1 + 2\n)\n");
+    /// for _ in (&mut chars).take(5) {}
+    /// let (rest, span) = chars.consume_to_end();
+    /// assert_eq!(rest, "\n)\n");
+    /// assert_eq!(format!("{span:#}"), "line 1 column 6 to line 3 column 1");
+    /// assert_eq!(chars.next(), None);
+    /// assert_eq!(chars.stats().lines, 3);
+    /// ```
+    pub fn consume_to_end(&mut self) -> (String, Span) {
+        let start = self.start_token();
+        let rest = if let Some(text) = self.bulk_text() {
+            let rest = text[self.current.byte..].to_string();
+            self.advance_in_bulk(&text, rest.len());
+            rest
+        } else {
+            self.by_ref().collect()
+        };
+        (rest, self.end_token(start))
+    }
+
+    /// Line and column of the end of the input, without consuming anything.
+    /// Worked out from the remaining text when the input is held in memory,
+    /// otherwise only known once the iterator is exhausted and None before
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("ab\ncd");
+    /// assert_eq!(
+    ///     chars.final_position(),
+    ///     Some(LineAndColumn { line: 2, column: 3 })
+    /// );
+    /// assert_eq!(chars.next(), Some('a'));
+    /// ```
+    pub fn final_position(&mut self) -> Option<LineAndColumn> {
+        let position = if self.peek().is_none() {
+            self.current
+        } else {
+            let text = self.bulk_text()?;
+            let mut end = self.current;
+            end.advance_over(&text[self.current.byte..], self.column_policy);
+            end
+        };
+        Some(LineAndColumn {
+            line: position.line,
+            column: position.col,
+        })
+    }
+
+    /// The input, if it is held in memory and nothing needs to see each
+    /// character as it is consumed, so the iterator can be advanced in bulk
+    pub(super) fn bulk_text(&self) -> Option<Arc<str>> {
+        self.text.clone().filter(|_| {
+            self.line_terminators == LineTerminators::LF
+                && self.feed.is_none()
+                && self.recording.is_none()
+                && self.hook.is_none()
+        })
+    }
+
+    /// Consume the next `len` bytes of `text` (from [Chars::bulk_text]) in
+    /// one step
+    pub(super) fn advance_in_bulk(&mut self, text: &Arc<str>, len: usize) {
+        let start = self.current.byte;
+        let skipped = &text[start..start + len];
+        let Some(last) = skipped.chars().next_back() else {
+            return;
+        };
+        self.line_starts
+            .extend(skipped.match_indices('\n').map(|(i, _)| start + i + 1));
+        self.current.advance_over(skipped, self.column_policy);
+        self.last_consumed = Some(last);
+        let it: super::Decoded = Box::new(TextChars {
+            text: Arc::clone(text),
+            byte: self.current.byte,
+            end: text.len(),
+        });
+        self.it = itertools::peek_nth(it);
+    }
+}
+
+impl Position {
+    /// Advance over `text`, which only has `\n` line terminators, looking
+    /// at each character only for the columns of the last line
+    fn advance_over(&mut self, text: &str, policy: super::ColumnPolicy) {
+        self.loc += text.chars().count();
+        self.byte += text.len();
+        self.after_cr = false;
+        let last_line = match text.rsplit_once('\n') {
+            Some((before, last_line)) => {
+                self.line += before.matches('\n').count() + 1;
+                self.col = 1;
+                last_line
+            }
+            None => text,
+        };
+        for c in last_line.chars() {
+            self.col += policy.width(c, self.col);
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::ColumnPolicy;
+
+    #[rstest]
+    #[case("")]
+    #[case("\n\n")]
+    #[case("a\t£😀\nb\t")]
+    fn bulk_matches_per_char(#[case] text: &str) {
+        let drain = |mut chars: Chars| {
+            let _ = chars.next();
+            let end = chars.final_position();
+            let rest = chars.consume_to_end();
+            (
+                rest,
+                end,
+                chars.final_position(),
+                chars.last_consumed(),
+                chars.position_of(text.len()),
+            )
+        };
+        let policy = ColumnPolicy::Visual { tab_width: 4 };
+        let bulk = drain(Chars::new(text).with_column_policy(policy));
+        let per_char = drain(
+            Chars::new(text)
+                .with_column_policy(policy)
+                .on_advance(|_| {}),
+        );
+        assert_eq!(bulk.0, per_char.0);
+        assert_eq!(bulk.1, bulk.2);
+        assert_eq!(bulk.2, per_char.2);
+        assert_eq!(bulk.3, per_char.3);
+        assert_eq!(bulk.4, per_char.4);
+    }
+
+    #[rstest]
+    #[case("a\t£😀\nb\t", 3)]
+    #[case("a\t£😀\nb\t", 5)]
+    #[case("a\t£😀\nb\t", 9)]
+    fn advance_by_matches_per_char(#[case] text: &str, #[case] n: usize) {
+        let advance = |mut chars: Chars| {
+            let result = chars.advance_by(n);
+            let span = chars.end_token(chars.start_token());
+            (result, span, chars.last_consumed(), chars.next())
+        };
+        let bulk = advance(Chars::new(text));
+        let per_char = advance(Chars::new(text).on_advance(|_| {}));
+        assert_eq!(bulk, per_char);
+    }
+}
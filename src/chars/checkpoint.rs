@@ -1,15 +1,24 @@
-use super::Chars;
+use super::{Chars, Position};
+use crate::Span;
 
 /// See [Chars::checkpoint]
 #[expect(missing_debug_implementations)]
 pub struct Checkpoint<'a> {
     chars: &'a mut Chars,
     peeked: usize,
+    /// Position after the characters returned so far
+    end: Position,
 }
 
 impl<'a> Checkpoint<'a> {
     pub(crate) fn new(chars: &'a mut Chars) -> Self {
-        Self { chars, peeked: 0 }
+        chars.stats.checkpoints += 1;
+        let end = chars.current;
+        Self {
+            chars,
+            peeked: 0,
+            end,
+        }
     }
 
     /// Releases the underlying [Chars] iterator with no changes. Identical to
@@ -19,6 +28,7 @@ impl<'a> Checkpoint<'a> {
     /// Commits the checkpoint by advancing the underlying [Chars] iterator
     /// across all of the characters returned by the checkpoint
     pub fn commit(self) {
+        self.chars.stats.commits += 1;
         for _ in self.chars.take(self.peeked) {}
     }
 
@@ -52,6 +62,56 @@ impl<'a> Checkpoint<'a> {
         s == head
     }
 
+    /// Return true if the next `n` characters all satisfy `pred`, e.g. to
+    /// check for exactly 4 hex digits. Unlike [Checkpoint::head_matches] the
+    /// characters are only peeked, so the checkpoint can carry on from the
+    /// same place whatever the result. Returns false if there are fewer than
+    /// `n` characters left
+    ///
+    /// ```
+    /// # use span::Chars;
+    /// let mut chars = Chars::new("u00e9!");
+    /// let mut checkpoint = chars.checkpoint();
+    /// assert_eq!(checkpoint.next(), Some('u'));
+    /// assert!(checkpoint.head_matches_class(4, |c| c.is_ascii_hexdigit()));
+    /// assert!(!checkpoint.head_matches_class(5, |c| c.is_ascii_hexdigit()));
+    /// assert!(!checkpoint.head_matches_class(6, |_| true));
+    /// assert_eq!(checkpoint.next(), Some('0'));
+    /// ```
+    pub fn head_matches_class(
+        &mut self,
+        n: usize,
+        mut pred: impl FnMut(char) -> bool,
+    ) -> bool {
+        (0..n).all(|i| {
+            self.chars.lookahead(self.peeked + i).is_some_and(&mut pred)
+        })
+    }
+
+    /// The span covering the characters returned by the checkpoint so far,
+    /// i.e. the span committing now would consume, e.g. to decide whether a
+    /// match is long enough to be worth committing
+    ///
+    /// ```
+    /// # use span::Chars;
+    /// let mut chars = Chars::new("ab\ncd");
+    /// let _ = chars.next();
+    /// let mut checkpoint = chars.checkpoint();
+    /// assert_eq!(format!("{}", checkpoint.span_so_far()), "line 1 column 2");
+    /// assert!(checkpoint.head_matches("b\nc"));
+    /// let span = checkpoint.span_so_far();
+    /// assert_eq!(format!("{span:#}"), "line 1 column 2 to line 2 column 2");
+    /// checkpoint.commit();
+    /// let start = chars.start_token();
+    /// assert_eq!(chars.next(), Some('d'));
+    /// let d = chars.end_token(start);
+    /// assert_eq!(span.end_line_and_column(), d.start_line_and_column());
+    /// ```
+    #[must_use]
+    pub fn span_so_far(&self) -> Span {
+        self.chars.current.span_to(self.end)
+    }
+
     /// Lookahead at the next item in the iterator without advancing. Peek
     /// always returns the same value until a call to next.
     ///
@@ -71,7 +131,49 @@ impl<'a> Checkpoint<'a> {
     /// assert_eq!(chars.next(), Some('3'));
     /// ```
     pub fn peek(&mut self) -> Option<char> {
-        self.chars.it.peek_nth(self.peeked).copied()
+        self.chars.lookahead(self.peeked)
+    }
+
+    /// Lookahead at the rest of the current line without advancing, see
+    /// [Chars::peek_line]
+    ///
+    /// ```
+    /// # use span::Chars;
+    /// let mut chars = Chars::new("// TODO: x\n");
+    /// let mut checkpoint = chars.checkpoint();
+    /// assert!(checkpoint.head_matches("// "));
+    /// assert_eq!(checkpoint.peek_line(), "TODO: x");
+    /// assert_eq!(checkpoint.next(), Some('T'));
+    /// ```
+    pub fn peek_line(&mut self) -> String {
+        self.chars.peek_line_from(self.peeked)
+    }
+
+    /// Number of characters before the first one satisfying `pred` without
+    /// advancing, see [Chars::peek_until]
+    ///
+    /// ```
+    /// # use span::Chars;
+    /// let mut chars = Chars::new("<<abc>>");
+    /// let mut checkpoint = chars.checkpoint();
+    /// assert!(checkpoint.head_matches("<<"));
+    /// assert_eq!(checkpoint.peek_until(|c| c == '>'), 3);
+    /// assert_eq!(checkpoint.peek_until_bounded(|c| c == '>', 3), None);
+    /// ```
+    pub fn peek_until(&mut self, pred: impl FnMut(char) -> bool) -> usize {
+        self.chars
+            .peek_until_from(self.peeked, usize::MAX, pred)
+            .unwrap_or_else(|remaining| remaining)
+    }
+
+    /// [Checkpoint::peek_until] looking at no more than `max` characters, see
+    /// [Chars::peek_until_bounded]
+    pub fn peek_until_bounded(
+        &mut self,
+        pred: impl FnMut(char) -> bool,
+        max: usize,
+    ) -> Option<usize> {
+        self.chars.peek_until_from(self.peeked, max, pred).ok()
     }
 }
 
@@ -79,7 +181,15 @@ impl Iterator for Checkpoint<'_> {
     type Item = char;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let result = self.chars.it.peek_nth(self.peeked).copied()?;
+        let result = self.chars.lookahead(self.peeked)?;
+        if let Some(&(c, bytes)) = self.chars.it.peek_nth(self.peeked) {
+            self.end.advance(
+                c,
+                bytes,
+                self.chars.column_policy,
+                self.chars.line_terminators,
+            );
+        }
         self.peeked += 1;
         Some(result)
     }
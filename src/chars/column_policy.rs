@@ -0,0 +1,64 @@
+use unicode_width::UnicodeWidthChar as _;
+
+/// What a column means. Configured on [Chars](super::Chars) with
+/// [Chars::with_column_policy](super::Chars::with_column_policy), every span
+/// produced afterwards (and so [Span](crate::Span)'s Display) uses the same
+/// units.
+///
+/// Lines always start at column 1 and a column is always the position
+/// *before* a character, so the policy decides how far each character moves
+/// the column along.
+///
+/// Spans don't record the policy they were made with, so the Span methods
+/// that work out new columns from the source text (e.g.
+/// [Span::lines](crate::Span::lines)) assume the default of
+/// [ColumnPolicy::Chars].
+///
+/// ```
+/// # use span::*;
+/// let columns = |policy| {
+///     let mut chars = Chars::new("\t£😀x").with_column_policy(policy);
+///     let _ = chars.by_ref().take(3).count();
+///     let start = chars.start_token();
+///     let _ = chars.next();
+///     chars.end_token(start).start_position_on_start_line().unwrap()
+/// };
+/// assert_eq!(columns(ColumnPolicy::Chars), 4);
+/// assert_eq!(columns(ColumnPolicy::Utf8), 8);
+/// assert_eq!(columns(ColumnPolicy::Utf16), 5);
+/// assert_eq!(columns(ColumnPolicy::Visual { tab_width: 4 }), 8);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColumnPolicy {
+    /// One column per character (Unicode scalar value)
+    #[default]
+    Chars,
+    /// One column per UTF-8 byte
+    Utf8,
+    /// One column per UTF-16 code unit, as used by LSP
+    Utf16,
+    /// The column the text would appear at in a terminal. Tabs advance to the
+    /// next multiple of `tab_width` and other characters take their
+    /// (unicode-width) display width
+    Visual {
+        /// Distance between tab stops
+        tab_width: usize,
+    },
+}
+
+impl ColumnPolicy {
+    /// Number of columns `c` takes up if it starts at `column` (1 indexed)
+    pub(crate) fn width(self, c: char, column: usize) -> usize {
+        match self {
+            ColumnPolicy::Chars => 1,
+            ColumnPolicy::Utf8 => c.len_utf8(),
+            ColumnPolicy::Utf16 => c.len_utf16(),
+            ColumnPolicy::Visual { tab_width } if c == '\t' => {
+                let tab_width = tab_width.max(1);
+                tab_width - (column - 1) % tab_width
+            }
+            ColumnPolicy::Visual { .. } => c.width().unwrap_or(0),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use super::Chars;
+use crate::Span;
+
+impl Chars {
+    /// If the input continues with `prefix` consume it and the rest of the
+    /// line and return the comment's span. The newline is left on the
+    /// iterator. Returns None without consuming anything if the input doesn't
+    /// start with `prefix`.
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("// note\nx");
+    /// assert_eq!(chars.skip_line_comment("#"), None);
+    /// let comment = chars.skip_line_comment("//").unwrap();
+    /// assert_eq!(format!("{comment:#}"), "line 1 column 1 to column 8");
+    /// assert_eq!(chars.next(), Some('\n'));
+    /// ```
+    pub fn skip_line_comment(&mut self, prefix: &str) -> Option<Span> {
+        if !self.head_matches(prefix) {
+            return None;
+        }
+        let start = self.start_token();
+        let _ = self.advance_by(prefix.chars().count());
+        let _ = self.skip_line();
+        Some(self.end_token(start))
+    }
+
+    /// Consume the rest of the current line and return its span. The line
+    /// terminator is left on the iterator.
+    ///
+    /// When the input is held in memory as UTF-8 and nothing needs to see
+    /// each character ([Chars::on_advance], [Chars::recording]) the newline
+    /// is found with a bulk search and the position updated in one step
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("# comment\nx");
+    /// let line = chars.skip_line();
+    /// assert_eq!(format!("{line:#}"), "line 1 column 1 to column 10");
+    /// assert_eq!(chars.last_consumed(), Some('t'));
+    /// assert_eq!(chars.next(), Some('\n'));
+    /// assert_eq!(format!("{:#}", chars.skip_line()), "line 2 column 1");
+    /// assert_eq!(chars.next(), None);
+    /// ```
+    pub fn skip_line(&mut self) -> Span {
+        let start = self.start_token();
+        if let Some(text) = self.bulk_text() {
+            let rest = &text[self.current.byte..];
+            let len = rest.find('\n').unwrap_or(rest.len());
+            self.advance_in_bulk(&text, len);
+        } else {
+            let terminators = self.line_terminators;
+            for _ in self.peek_while(|c| !terminators.ends_line(c)) {}
+        }
+        self.end_token(start)
+    }
+
+    /// If the input continues with `open` consume a block comment up to and
+    /// including the matching `close`. With `nested` each `open` inside the
+    /// comment needs its own `close`.
+    ///
+    /// Returns None without consuming anything if the input doesn't start
+    /// with `open`, or if `open` or `close` is empty. An unterminated comment consumes the rest of the input
+    /// and its span is returned as the error.
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("/* a /* b */ c */d");
+    /// let comment = chars.skip_block_comment("/*", "*/", true);
+    /// assert_eq!(
+    ///     comment.map(|c| c.map(|span| format!("{span:#}"))),
+    ///     Some(Ok(String::from("line 1 column 1 to column 18")))
+    /// );
+    /// assert_eq!(chars.next(), Some('d'));
+    ///
+    /// let mut chars = Chars::new("(* a\n(* b *)");
+    /// let Some(Err(span)) = chars.skip_block_comment("(*", "*)", true) else {
+    ///     panic!()
+    /// };
+    /// assert_eq!(format!("{span:#}"), "line 1 column 1 to line 2 column 8");
+    /// ```
+    pub fn skip_block_comment(
+        &mut self,
+        open: &str,
+        close: &str,
+        nested: bool,
+    ) -> Option<Result<Span, Span>> {
+        if open.is_empty() || close.is_empty() || !self.head_matches(open) {
+            return None;
+        }
+        let (open_len, close_len) =
+            (open.chars().count(), close.chars().count());
+        let start = self.start_token();
+        let _ = self.advance_by(open_len);
+        let mut depth = 1;
+        while self.peek().is_some() {
+            if self.head_matches(close) {
+                let _ = self.advance_by(close_len);
+                depth -= 1;
+                if depth == 0 {
+                    return Some(Ok(self.end_token(start)));
+                }
+            } else if nested && self.head_matches(open) {
+                let _ = self.advance_by(open_len);
+                depth += 1;
+            } else {
+                let _ = self.next();
+            }
+        }
+        Some(Err(self.end_token(start)))
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::ColumnPolicy;
+
+    #[rstest]
+    #[case("", "*/", true)]
+    #[case("/*", "", false)]
+    fn empty_block_comment_delimiters(
+        #[case] open: &str,
+        #[case] close: &str,
+        #[case] nested: bool,
+    ) {
+        let mut chars = Chars::new("/* a */");
+        assert_eq!(chars.skip_block_comment(open, close, nested), None);
+        assert_eq!(chars.next(), Some('/'));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("\n")]
+    #[case("a\t£😀\nb")]
+    #[case("no newline")]
+    fn skip_line_fast_path_matches_slow_path(#[case] text: &str) {
+        let skip = |mut chars: Chars| {
+            let _ = chars.next();
+            let line = chars.skip_line();
+            let start = chars.start_token();
+            let next = chars.next();
+            (line, chars.last_consumed(), next, chars.end_token(start))
+        };
+        let policy = ColumnPolicy::Visual { tab_width: 4 };
+        assert_eq!(
+            skip(Chars::new(text).with_column_policy(policy)),
+            skip(
+                Chars::new(text)
+                    .with_column_policy(policy)
+                    .on_advance(|_| {})
+            )
+        );
+    }
+
+    #[test]
+    fn block_comments_only_nest_when_asked() {
+        let mut chars = Chars::new("/* /* */ x */");
+        let comment = chars.skip_block_comment("/*", "*/", false);
+        assert!(matches!(comment, Some(Ok(_))));
+        assert_eq!(chars.collect::<String>(), " x */");
+    }
+
+    #[test]
+    fn line_comment_at_end_of_input() {
+        let mut chars = Chars::new("#");
+        let comment = chars.skip_line_comment("#").unwrap();
+        assert_eq!(comment.len_chars(), Some(1));
+        assert_eq!(chars.next(), None);
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+
+use encoding_rs::{Decoder, Encoding};
+
+use super::Chars;
+
+impl Chars {
+    /// Decode `bytes` from `encoding` as they are consumed. Absolute byte
+    /// offsets in the resulting spans index `bytes` rather than the decoded
+    /// text, so they can be used to slice the raw input.
+    ///
+    /// A byte order mark at the start of the input overrides `encoding` and
+    /// is skipped (spans begin after it). Malformed sequences decode to
+    /// U+FFFD.
+    ///
+    /// ```
+    /// # use span::*;
+    /// let latin1 = b"caf\xe9 x";
+    /// let mut chars = Chars::from_bytes(latin1, encoding_rs::WINDOWS_1252);
+    /// assert_eq!((&mut chars).take(5).collect::<String>(), "café ");
+    /// let start = chars.start_token();
+    /// assert_eq!(chars.next(), Some('x'));
+    /// let span = chars.end_token(start);
+    /// assert_eq!(span.display_bytes().to_string(), "bytes 5..6");
+    ///
+    /// let utf16 = b"\xff\xfeh\0i\0";
+    /// let mut chars = Chars::from_bytes(utf16, encoding_rs::WINDOWS_1252);
+    /// assert_eq!(chars.next(), Some('h'));
+    /// let start = chars.start_token();
+    /// assert_eq!(chars.next(), Some('i'));
+    /// let span = chars.end_token(start);
+    /// assert_eq!(span.display_bytes().to_string(), "bytes 4..6");
+    /// ```
+    #[must_use]
+    pub fn from_bytes(
+        bytes: impl Into<Vec<u8>>,
+        encoding: &'static Encoding,
+    ) -> Self {
+        let bytes = bytes.into();
+        let (encoding, bom) =
+            Encoding::for_bom(&bytes).unwrap_or((encoding, 0));
+        let decode = Decode {
+            bytes: bytes.into_iter().skip(bom),
+            decoder: encoding.new_decoder_without_bom_handling(),
+            pending: 0,
+            output: String::new(),
+            ready: VecDeque::new(),
+            finished: false,
+        };
+        Chars::from_decoded(decode, bom)
+    }
+}
+
+/// Feeds the decoder one byte at a time so each character can be matched up
+/// with the bytes that produced it
+struct Decode {
+    bytes: std::iter::Skip<std::vec::IntoIter<u8>>,
+    decoder: Decoder,
+    /// Bytes fed to the decoder since it last produced a character
+    pending: usize,
+    output: String,
+    ready: VecDeque<(char, usize)>,
+    finished: bool,
+}
+
+impl Iterator for Decode {
+    type Item = (char, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(next) = self.ready.pop_front() {
+                return Some(next);
+            }
+            if self.finished {
+                return None;
+            }
+            let byte = self.bytes.next();
+            self.finished = byte.is_none();
+            let input = byte.as_slice();
+            self.pending += input.len();
+            self.output.clear();
+            self.output.reserve(
+                self.decoder
+                    .max_utf8_buffer_length(input.len())
+                    .unwrap_or(16),
+            );
+            let _ = self.decoder.decode_to_string(
+                input,
+                &mut self.output,
+                self.finished,
+            );
+            // If one byte completes several characters (e.g. a malformed
+            // sequence followed by a valid one) the bytes are all attributed
+            // to the first
+            for c in self.output.chars() {
+                self.ready.push_back((c, self.pending));
+                self.pending = 0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn shift_jis_offsets_index_raw_bytes() {
+        let (bytes, _, _) = encoding_rs::SHIFT_JIS.encode("aあb");
+        let mut chars = Chars::from_bytes(bytes, encoding_rs::SHIFT_JIS);
+        assert_eq!(chars.next(), Some('a'));
+        let start = chars.start_token();
+        assert_eq!(chars.next(), Some('あ'));
+        let span = chars.end_token(start);
+        assert_eq!((span.start_byte(), span.len_bytes()), (Some(1), Some(2)));
+        assert_eq!(span.len_chars(), Some(1));
+        assert_eq!(chars.next(), Some('b'));
+        assert_eq!(chars.next(), None);
+    }
+
+    #[test]
+    fn malformed_input_is_replaced() {
+        let chars = Chars::from_bytes(b"\xc3(", encoding_rs::UTF_8);
+        assert_eq!(chars.collect::<String>(), "\u{fffd}(");
+    }
+}
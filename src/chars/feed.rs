@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+
+use super::{Chars, Decoded};
+
+/// State of an incremental iterator, see [Chars::incremental]
+pub(super) struct Feed {
+    finished: bool,
+}
+
+impl Chars {
+    /// Iterator over input that arrives in pieces (e.g. from the network),
+    /// supplied with [Chars::feed]. Positions carry on across pieces so a
+    /// token can span several of them.
+    ///
+    /// Until [Chars::finish] is called running out of characters only means
+    /// the input fed so far has been consumed: a lexer should abort any
+    /// [Checkpoint](super::Checkpoint) for an incomplete token and try again
+    /// after the next piece arrives. See [Chars::is_finished].
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::incremental();
+    /// chars.feed("ab");
+    /// let _ = chars.next();
+    /// let start = chars.start_token();
+    /// assert_eq!(chars.next(), Some('b'));
+    /// assert_eq!(chars.next(), None);
+    /// assert!(!chars.is_finished());
+    ///
+    /// chars.feed("\ncd");
+    /// chars.finish();
+    /// assert_eq!(chars.by_ref().take(2).collect::<String>(), "\nc");
+    /// let span = chars.end_token(start);
+    /// assert_eq!(format!("{span:#}"), "line 1 column 2 to line 2 column 2");
+    /// assert_eq!(chars.next(), Some('d'));
+    /// assert_eq!(chars.next(), None);
+    /// assert!(chars.is_finished());
+    /// ```
+    #[must_use]
+    pub fn incremental() -> Self {
+        let mut chars = Chars::from_decoded(std::iter::empty(), 0);
+        chars.feed = Some(Feed { finished: false });
+        chars
+    }
+
+    /// Append `chunk` to the input of an iterator created with
+    /// [Chars::incremental]. Takes time proportional to the input that has
+    /// been fed but not consumed yet, as well as to `chunk`.
+    ///
+    /// Feeding an iterator that isn't incremental or has been finished is a
+    /// bug. In debug it panics but in release the chunk is ignored
+    pub fn feed(&mut self, chunk: &str) {
+        match &self.feed {
+            None => {
+                debug_assert!(
+                    false,
+                    "feed called on an iterator not created with \
+                     Chars::incremental"
+                );
+                return;
+            }
+            Some(feed) if feed.finished => {
+                debug_assert!(false, "feed called on a finished iterator");
+                return;
+            }
+            Some(_) => {}
+        }
+        // The underlying iterator is fused once it runs dry, so replace it
+        // with one over whatever hasn't been consumed yet plus the new chunk
+        let mut pending = self.it.by_ref().collect::<VecDeque<_>>();
+        pending.extend(chunk.chars().map(|c| (c, c.len_utf8())));
+        let it: Decoded = Box::new(pending.into_iter());
+        self.it = itertools::peek_nth(it);
+    }
+
+    /// Mark the end of the input of an iterator created with
+    /// [Chars::incremental]
+    pub fn finish(&mut self) {
+        if let Some(feed) = &mut self.feed {
+            feed.finished = true;
+        }
+    }
+
+    /// Whether running out of characters means the end of the input, which
+    /// is always the case unless the iterator was created with
+    /// [Chars::incremental] and [Chars::finish] hasn't been called
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.feed.as_ref().is_none_or(|feed| feed.finished)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic = "feed called on an iterator not created with \
+                      Chars::incremental"]
+    fn feed_not_incremental() {
+        Chars::new("a").feed("b");
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic = "feed called on a finished iterator"]
+    fn feed_finished() {
+        let mut chars = Chars::incremental();
+        chars.finish();
+        chars.feed("b");
+    }
+
+    #[test]
+    fn retry_incomplete_token() {
+        let mut chars = Chars::incremental();
+        let mut tokens = Vec::new();
+        for chunk in ["ab", "c d", "ef"] {
+            chars.feed(chunk);
+            loop {
+                let _ = chars.skip_whitespace();
+                let mut checkpoint = chars.checkpoint();
+                let mut token = String::new();
+                while let Some(c) =
+                    checkpoint.peek().filter(|c| !c.is_whitespace())
+                {
+                    token.push(c);
+                    let _ = checkpoint.next();
+                }
+                if checkpoint.peek().is_none() {
+                    checkpoint.abort();
+                    break;
+                }
+                checkpoint.commit();
+                tokens.push(token);
+            }
+        }
+        chars.finish();
+        tokens.push(chars.by_ref().collect());
+        assert_eq!(tokens, ["abc", "def"]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic = "feed called on a finished iterator"]
+    fn feed_after_finish() {
+        let mut chars = Chars::incremental();
+        chars.finish();
+        chars.feed("x");
+    }
+}
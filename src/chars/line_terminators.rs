@@ -0,0 +1,69 @@
+/// Which characters end a line. Configured on [Chars](super::Chars) with
+/// [Chars::with_line_terminators](super::Chars::with_line_terminators), the
+/// default only recognizes `\n`.
+///
+/// `\r\n` always counts as a single line ending, the `\n` doesn't start
+/// another line if the `\r` already did.
+///
+/// Like [ColumnPolicy](super::ColumnPolicy) spans don't record which
+/// terminators they were made with, so the Span methods that work from the
+/// source text (e.g. [Span::lines](crate::Span::lines)) only split on `\n`.
+///
+/// ```
+/// # use span::*;
+/// let last_line = |terminators| {
+///     let mut chars = Chars::new("a\rb\r\nc\u{2028}d")
+///         .with_line_terminators(terminators);
+///     let _ = chars.by_ref().take(7).count();
+///     let start = chars.start_token();
+///     let _ = chars.next();
+///     chars.end_token(start).start_line().unwrap()
+/// };
+/// assert_eq!(last_line(LineTerminators::LF), 2);
+/// assert_eq!(last_line(LineTerminators::UNICODE), 4);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[expect(clippy::struct_excessive_bools)]
+pub struct LineTerminators {
+    /// A `\r` on its own (old Mac OS line endings)
+    pub carriage_return: bool,
+    /// U+0085 NEXT LINE
+    pub next_line: bool,
+    /// U+2028 LINE SEPARATOR
+    pub line_separator: bool,
+    /// U+2029 PARAGRAPH SEPARATOR
+    pub paragraph_separator: bool,
+}
+
+impl LineTerminators {
+    /// Only `\n`
+    pub const LF: LineTerminators = LineTerminators {
+        carriage_return: false,
+        next_line: false,
+        line_separator: false,
+        paragraph_separator: false,
+    };
+
+    /// Every line terminator recognized by Unicode (UAX #14 mandatory
+    /// breaks, other than form feed and vertical tab), matching what most
+    /// editors show
+    pub const UNICODE: LineTerminators = LineTerminators {
+        carriage_return: true,
+        next_line: true,
+        line_separator: true,
+        paragraph_separator: true,
+    };
+
+    /// Whether `c` starts a new line. `\n` always does
+    pub(crate) fn ends_line(self, c: char) -> bool {
+        match c {
+            '\n' => true,
+            '\r' => self.carriage_return,
+            '\u{85}' => self.next_line,
+            '\u{2028}' => self.line_separator,
+            '\u{2029}' => self.paragraph_separator,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+
+use super::Chars;
+use crate::Span;
+
+/// Lookahead beyond the limit set with [Chars::with_lookahead_limit]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LookaheadExceeded {
+    /// The configured limit
+    pub limit: usize,
+    /// How many characters ahead were inspected
+    pub depth: usize,
+    /// The characters that were inspected, from the head of the iterator
+    pub span: Span,
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for LookaheadExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:#}: lookahead of {} characters exceeds the limit of {}",
+            self.span, self.depth, self.limit
+        )
+    }
+}
+
+impl std::error::Error for LookaheadExceeded {}
+
+impl Chars {
+    /// Limit how far ahead of the head of the iterator can be inspected, by
+    /// peeking or by a [Checkpoint](super::Checkpoint), e.g. to keep a
+    /// grammar LL(k). Exceeding the limit panics in debug builds. In release
+    /// builds the lookahead is allowed and the first violation is available
+    /// from [Chars::lookahead_exceeded].
+    ///
+    /// ```should_panic
+    /// # use span::*;
+    /// let mut chars = Chars::new("abc").with_lookahead_limit(2);
+    /// let mut checkpoint = chars.checkpoint();
+    /// assert!(checkpoint.head_matches("ab"));
+    /// let _ = checkpoint.next();
+    /// ```
+    #[must_use]
+    pub fn with_lookahead_limit(mut self, limit: usize) -> Self {
+        self.lookahead_limit = Some(limit);
+        self
+    }
+
+    /// The first lookahead beyond the limit set with
+    /// [Chars::with_lookahead_limit], if there has been one
+    #[must_use]
+    pub fn lookahead_exceeded(&self) -> Option<LookaheadExceeded> {
+        self.lookahead_exceeded
+    }
+
+    /// Record (and in debug builds panic on) lookahead of `depth` characters
+    /// if it is over the limit
+    pub(super) fn check_lookahead(&mut self, depth: usize) {
+        let Some(limit) = self.lookahead_limit else {
+            return;
+        };
+        if depth <= limit || self.lookahead_exceeded.is_some() {
+            return;
+        }
+        let mut end = self.current;
+        for i in 0..depth {
+            let Some(&(c, bytes)) = self.it.peek_nth(i) else {
+                break;
+            };
+            end.advance(c, bytes, self.column_policy, self.line_terminators);
+        }
+        let exceeded = LookaheadExceeded {
+            limit,
+            depth,
+            span: self.current.span_to(end),
+        };
+        self.lookahead_exceeded = Some(exceeded);
+        #[cfg(debug_assertions)]
+        panic!("{exceeded}");
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn within_limit() {
+        let mut chars = Chars::new("abc").with_lookahead_limit(2);
+        let mut checkpoint = chars.checkpoint();
+        assert!(checkpoint.head_matches("ab"));
+        checkpoint.commit();
+        assert_eq!(chars.peek(), Some('c'));
+        assert_eq!(chars.lookahead_exceeded(), None);
+    }
+
+    #[test]
+    fn peek_until_bounded_stays_within_limit() {
+        let mut chars = Chars::new("abcdef").with_lookahead_limit(3);
+        assert_eq!(chars.peek_until_bounded(|c| c == 'f', 3), None);
+        assert_eq!(chars.peek_until_bounded(|c| c == 'c', 3), Some(2));
+        assert_eq!(chars.lookahead_exceeded(), None);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic = "line 1 column 1 to column 4: lookahead of 3 characters \
+                      exceeds the limit of 2"]
+    fn reports_span() {
+        let mut chars = Chars::new("abcd").with_lookahead_limit(2);
+        let _ = chars.peek_with(|lookahead| lookahead.nth(2));
+    }
+}
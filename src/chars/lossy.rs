@@ -0,0 +1,109 @@
+use std::{char::REPLACEMENT_CHARACTER, fmt};
+
+use super::Chars;
+use crate::Span;
+
+/// An invalid UTF-8 sequence replaced by U+FFFD, see
+/// [Chars::from_utf8_lossy]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InvalidUtf8 {
+    /// The bytes that were replaced
+    pub bytes: Vec<u8>,
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for InvalidUtf8 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid UTF-8 sequence")?;
+        for byte in &self.bytes {
+            write!(f, " {byte:#04x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for InvalidUtf8 {}
+
+impl Chars {
+    /// Iterate over possibly invalid UTF-8, replacing each invalid sequence
+    /// with U+FFFD in the same way as [String::from_utf8_lossy]. Absolute byte
+    /// offsets index `bytes`, so a replacement character covers all of the
+    /// bytes it replaced.
+    ///
+    /// Also returns the span and contents of each invalid sequence. Their
+    /// columns are counted with the default [ColumnPolicy](super::ColumnPolicy)
+    ///
+    /// ```
+    /// # use span::*;
+    /// let (chars, errors) = Chars::from_utf8_lossy(b"ok\n\xffno\xe2\x82!");
+    /// assert_eq!(chars.collect::<String>(), "ok\n\u{fffd}no\u{fffd}!");
+    /// let errors = errors
+    ///     .iter()
+    ///     .map(|(span, e)| format!("{span}: {e}"))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     errors,
+    ///     [
+    ///         "line 2 column 1: invalid UTF-8 sequence 0xff",
+    ///         "line 2 column 4: invalid UTF-8 sequence 0xe2 0x82",
+    ///     ]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn from_utf8_lossy(
+        bytes: impl Into<Vec<u8>>,
+    ) -> (Self, Vec<(Span, InvalidUtf8)>) {
+        let bytes = bytes.into();
+        let mut decoded = Vec::new();
+        let mut invalid = Vec::new();
+        for chunk in bytes.utf8_chunks() {
+            decoded.extend(chunk.valid().chars().map(|c| (c, c.len_utf8())));
+            if !chunk.invalid().is_empty() {
+                invalid.push((decoded.len(), chunk.invalid().to_vec()));
+                decoded.push((REPLACEMENT_CHARACTER, chunk.invalid().len()));
+            }
+        }
+
+        let mut scan = Chars::from_decoded(decoded.clone().into_iter(), 0);
+        let mut consumed = 0;
+        let errors = invalid
+            .into_iter()
+            .map(|(index, bytes)| {
+                let _ = scan.advance_by(index - consumed);
+                let start = scan.start_token();
+                let _ = scan.next();
+                consumed = index + 1;
+                (scan.end_token(start), InvalidUtf8 { bytes })
+            })
+            .collect();
+        (Chars::from_decoded(decoded.into_iter(), 0), errors)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn replacement_covers_invalid_bytes() {
+        let (mut chars, errors) = Chars::from_utf8_lossy(b"a\xf0\x9f\x98b");
+        assert_eq!(chars.next(), Some('a'));
+        let start = chars.start_token();
+        assert_eq!(chars.next(), Some(REPLACEMENT_CHARACTER));
+        let span = chars.end_token(start);
+        assert_eq!(span.display_bytes().to_string(), "bytes 1..4");
+        assert_eq!(errors[0].0, span);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(chars.next(), Some('b'));
+    }
+
+    #[test]
+    fn valid_input() {
+        let (chars, errors) = Chars::from_utf8_lossy("£\u{fffd}");
+        assert_eq!(chars.collect::<String>(), "£\u{fffd}");
+        assert_eq!(errors, []);
+    }
+}
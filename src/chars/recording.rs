@@ -0,0 +1,129 @@
+use super::Chars;
+use crate::Span;
+
+/// Buffer of consumed characters, see [Chars::recording]
+pub(super) struct Recording {
+    text: String,
+    /// Absolute character offset of the start of `text`
+    first: usize,
+    /// Number of characters in `text`
+    len: usize,
+    limit: Option<usize>,
+}
+
+impl Recording {
+    pub(super) fn push(&mut self, c: char) {
+        self.text.push(c);
+        self.len += 1;
+        // Drop the oldest half at once so trimming stays amortised O(1)
+        if let Some(limit) = self.limit
+            && self.len > limit.saturating_mul(2).max(1)
+        {
+            let excess = self.len - limit;
+            let cut = self
+                .text
+                .char_indices()
+                .nth(excess)
+                .map_or(self.text.len(), |(i, _)| i);
+            let _ = self.text.drain(..cut);
+            self.first += excess;
+            self.len = limit;
+        }
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.text.clear();
+        self.first = 0;
+        self.len = 0;
+    }
+}
+
+impl Chars {
+    /// Keep the consumed text so it can be retrieved by span with
+    /// [Chars::recorded_slice], for input that can't be sliced later (e.g. a
+    /// stream). With a `limit` only (at least) the most recent `limit`
+    /// characters are kept.
+    ///
+    /// Characters consumed before recording starts aren't retained.
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("let x = 1;").recording(None);
+    /// assert_eq!(chars.advance_by(4), Ok(()));
+    /// let start = chars.start_token();
+    /// let _ = chars.next();
+    /// let span = chars.end_token(start);
+    /// assert_eq!(chars.recorded_slice(&span), Some("x"));
+    /// assert_eq!(chars.recorded_slice(&Span::UNKNOWN), None);
+    /// ```
+    #[must_use]
+    pub fn recording(mut self, limit: Option<usize>) -> Self {
+        self.recording = Some(Recording {
+            text: String::new(),
+            first: self.current.loc,
+            len: 0,
+            limit,
+        });
+        self
+    }
+
+    /// The recorded text covered by `span`, or None if recording isn't
+    /// enabled or any of the span's text isn't in the buffer (not consumed
+    /// yet, or dropped because of the limit)
+    #[must_use]
+    pub fn recorded_slice(&self, span: &Span) -> Option<&str> {
+        let recording = self.recording.as_ref()?;
+        let absolute = span.absolute?;
+        let start = absolute.start.checked_sub(recording.first)?;
+        let end = absolute.end.checked_sub(recording.first)?;
+        if end > recording.len || start > end {
+            return None;
+        }
+        let mut offsets = recording
+            .text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([recording.text.len()]);
+        let start_byte = offsets.nth(start)?;
+        let end_byte = if end == start {
+            start_byte
+        } else {
+            offsets.nth(end - start - 1)?
+        };
+        Some(&recording.text[start_byte..end_byte])
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn limit_drops_old_text() {
+        let mut chars = Chars::new("£bcdefgh").recording(Some(2));
+        let start = chars.start_token();
+        let _ = chars.next();
+        let first = chars.end_token(start);
+        assert_eq!(chars.recorded_slice(&first), Some("£"));
+        assert_eq!(chars.advance_by(6), Ok(()));
+        let start = chars.start_token();
+        let _ = chars.next();
+        let last = chars.end_token(start);
+        assert_eq!(chars.recorded_slice(&first), None);
+        assert_eq!(chars.recorded_slice(&last), Some("h"));
+    }
+
+    #[test]
+    fn starts_at_current_position() {
+        let mut chars = Chars::new("abc");
+        let start = chars.start_token();
+        let _ = chars.next();
+        let mut chars = chars.recording(None);
+        let _ = chars.next();
+        let span = chars.end_token(start);
+        assert_eq!(chars.recorded_slice(&span), None);
+    }
+}
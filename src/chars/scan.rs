@@ -0,0 +1,241 @@
+use std::fmt;
+
+use itertools::PeekingNext as _;
+
+use super::{Chars, TokenHandle};
+use crate::Span;
+
+/// Errors produced by [Chars::scan_integer] and [Chars::scan_float]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MalformedNumber {
+    /// A radix prefix or exponent with no digits after it, the span covers
+    /// the whole literal
+    MissingDigits(Span),
+    /// A character that isn't a valid digit in the literal's radix
+    InvalidDigit(char, Span),
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for MalformedNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MalformedNumber::MissingDigits(span) => {
+                write!(f, "{span}: expected digits")
+            }
+            MalformedNumber::InvalidDigit(c, span) => {
+                write!(f, "{span}: invalid digit {c:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MalformedNumber {}
+
+impl Chars {
+    /// Consume an identifier whose first character satisfies `is_start` and
+    /// whose remaining characters satisfy `is_continue`, returning its text
+    /// and span. Returns None without consuming anything if the next
+    /// character doesn't satisfy `is_start`.
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("_foo1 bar");
+    /// let (ident, span) = chars
+    ///     .scan_identifier(
+    ///         |c| c == '_' || c.is_alphabetic(),
+    ///         |c| c == '_' || c.is_alphanumeric(),
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(ident, "_foo1");
+    /// assert_eq!(format!("{span:#}"), "line 1 column 1 to column 6");
+    /// assert_eq!(chars.next(), Some(' '));
+    /// ```
+    pub fn scan_identifier(
+        &mut self,
+        is_start: impl Fn(char) -> bool,
+        is_continue: impl Fn(char) -> bool,
+    ) -> Option<(String, Span)> {
+        if !self.peek().is_some_and(is_start) {
+            return None;
+        }
+        let start = self.start_token();
+        let mut text = String::new();
+        text.extend(self.next());
+        text.extend(self.peek_while(is_continue));
+        Some((text, self.end_token(start)))
+    }
+
+    /// Consume an integer literal, returning its text (as written) and span.
+    /// `0x`, `0o` and `0b` prefixes select the radix and `_` may be used to
+    /// separate digits. Returns None without consuming anything if the next
+    /// character isn't an ASCII digit.
+    ///
+    /// Any letters directly following the literal are consumed as part of it
+    /// and reported as invalid digits.
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("0xff_ff 0b102 0x");
+    /// let (text, _) = chars.scan_integer().unwrap().unwrap();
+    /// assert_eq!(text, "0xff_ff");
+    /// chars.skip_whitespace();
+    /// let Err(e) = chars.scan_integer().unwrap() else { panic!() };
+    /// assert_eq!(e.to_string(), "line 1 column 13: invalid digit '2'");
+    /// chars.skip_whitespace();
+    /// let Err(e) = chars.scan_integer().unwrap() else { panic!() };
+    /// assert_eq!(e.to_string(), "line 1 column 15: expected digits");
+    /// ```
+    pub fn scan_integer(
+        &mut self,
+    ) -> Option<Result<(String, Span), MalformedNumber>> {
+        if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let start = self.start_token();
+        let mut text = String::new();
+        let radix = match (self.lookahead(0), self.lookahead(1)) {
+            (Some('0'), Some(prefix @ ('x' | 'X' | 'o' | 'O' | 'b' | 'B'))) => {
+                let _ = self.advance_by(2);
+                text.push('0');
+                text.push(prefix);
+                match prefix {
+                    'x' | 'X' => 16,
+                    'o' | 'O' => 8,
+                    _ => 2,
+                }
+            }
+            _ => 10,
+        };
+        let digits = self.digits(&mut text, radix, |c| {
+            c == '_' || c.is_ascii_alphanumeric()
+        });
+        Some(self.finish_number(text, start, digits))
+    }
+
+    /// Consume a decimal floating point literal: digits, then optionally a
+    /// `.` followed by digits, then optionally an exponent (`e` or `E`, an
+    /// optional sign and digits). `_` may be used to separate digits. Returns
+    /// None without consuming anything if the next character isn't an ASCII
+    /// digit.
+    ///
+    /// A `.` that isn't followed by a digit is left on the iterator so `1..2`
+    /// and `1.max(2)` aren't mistaken for floats.
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("6.02_2e+23 1.max 1e");
+    /// let (text, _) = chars.scan_float().unwrap().unwrap();
+    /// assert_eq!(text, "6.02_2e+23");
+    /// chars.skip_whitespace();
+    /// let (text, _) = chars.scan_float().unwrap().unwrap();
+    /// assert_eq!(text, "1");
+    /// assert_eq!(chars.next(), Some('.'));
+    /// for _ in chars.peek_while(|c| c != ' ') {}
+    /// chars.skip_whitespace();
+    /// let Err(e) = chars.scan_float().unwrap() else { panic!() };
+    /// assert_eq!(e.to_string(), "line 1 column 18: expected digits");
+    /// ```
+    pub fn scan_float(
+        &mut self,
+    ) -> Option<Result<(String, Span), MalformedNumber>> {
+        if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let start = self.start_token();
+        let mut text = String::new();
+        let is_digit = |c: char| c == '_' || c.is_ascii_digit();
+        let mut digits = self.digits(&mut text, 10, is_digit);
+        if self.lookahead(0) == Some('.')
+            && self.lookahead(1).is_some_and(|c| c.is_ascii_digit())
+        {
+            text.extend(self.next());
+            digits = digits.and(self.digits(&mut text, 10, is_digit));
+        }
+        if let Some(e @ ('e' | 'E')) = self.peek() {
+            let _ = self.next();
+            text.push(e);
+            text.extend(self.peeking_next(|&c| c == '+' || c == '-'));
+            digits = digits.and(self.digits(&mut text, 10, is_digit));
+        }
+        Some(self.finish_number(text, start, digits))
+    }
+
+    /// Consume characters satisfying `consume` into `text`. Returns Ok(false)
+    /// if there were no digits, or the first character that isn't a digit in
+    /// `radix` (other than `_`) with its span
+    fn digits(
+        &mut self,
+        text: &mut String,
+        radix: u32,
+        consume: impl Fn(char) -> bool,
+    ) -> Result<bool, (char, Span)> {
+        let mut result = Ok(false);
+        while let Some(c) = self.peek().filter(|&c| consume(c)) {
+            let start = self.start_token();
+            let _ = self.next();
+            text.push(c);
+            if c.is_digit(radix) {
+                result = result.map(|_| true);
+            } else if c != '_' && result.is_ok() {
+                result = Err((c, self.end_token(start)));
+            }
+        }
+        result
+    }
+
+    fn finish_number(
+        &mut self,
+        text: String,
+        start: TokenHandle,
+        digits: Result<bool, (char, Span)>,
+    ) -> Result<(String, Span), MalformedNumber> {
+        let span = self.end_token(start);
+        match digits {
+            Ok(true) => Ok((text, span)),
+            Ok(false) => Err(MalformedNumber::MissingDigits(span)),
+            Err((c, span)) => Err(MalformedNumber::InvalidDigit(c, span)),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("0", Some("0"))]
+    #[case("1_000)", Some("1_000"))]
+    #[case("0o17", Some("0o17"))]
+    #[case("0B1_0", Some("0B1_0"))]
+    #[case("0o8", None)]
+    #[case("12ab", None)]
+    #[case("0x_", None)]
+    fn integers(#[case] source: &str, #[case] expected: Option<&str>) {
+        let mut chars = Chars::new(source);
+        let scanned = chars.scan_integer().unwrap().ok();
+        assert_eq!(scanned.as_ref().map(|(text, _)| text.as_str()), expected);
+    }
+
+    #[rstest]
+    #[case("1.5", Some("1.5"))]
+    #[case("1_0.0_1E-3", Some("1_0.0_1E-3"))]
+    #[case("1.", Some("1"))]
+    #[case("2e+", None)]
+    fn floats(#[case] source: &str, #[case] expected: Option<&str>) {
+        let mut chars = Chars::new(source);
+        let scanned = chars.scan_float().unwrap().ok();
+        assert_eq!(scanned.as_ref().map(|(text, _)| text.as_str()), expected);
+    }
+
+    #[test]
+    fn not_a_number() {
+        let mut chars = Chars::new("x1");
+        assert_eq!(chars.scan_integer(), None);
+        assert_eq!(chars.scan_float(), None);
+        assert_eq!(chars.next(), Some('x'));
+    }
+}
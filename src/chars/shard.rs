@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use super::{Chars, Position, TextChars};
+
+impl Chars {
+    /// Split `text` into at most `n` iterators over consecutive runs of whole
+    /// lines of roughly equal size, so the shards can be lexed independently
+    /// (e.g. in parallel) and their tokens concatenated. Each shard starts
+    /// with the position it has in `text`, so it produces exactly the spans
+    /// iterating over the whole of `text` would.
+    ///
+    /// A shard only covers its own lines, so [Chars::fork] and
+    /// [Chars::position_of] aren't available on it. Returns a single empty
+    /// iterator for empty text.
+    ///
+    /// ```
+    /// # use span::*;
+    /// let shards = Chars::split_at_lines("a\nbb\ncc\nd", 2);
+    /// assert_eq!(shards.len(), 2);
+    /// let mut second = shards.into_iter().nth(1).unwrap();
+    /// let start = second.start_token();
+    /// assert_eq!(second.next(), Some('c'));
+    /// let span = second.end_token(start);
+    /// assert_eq!(format!("{span}"), "line 3 column 1");
+    /// assert_eq!(span.start(), Some(5));
+    /// ```
+    #[must_use]
+    pub fn split_at_lines(text: impl Into<Arc<str>>, n: usize) -> Vec<Chars> {
+        let text = text.into();
+        // There can't be more non-empty shards than bytes
+        let n = n.clamp(1, text.len().max(1));
+        let mut shards = Vec::with_capacity(n);
+        let mut start = Position {
+            loc: 0,
+            byte: 0,
+            line: 1,
+            col: 1,
+            after_cr: false,
+        };
+        for i in 1..=n {
+            // '\n' is never part of a multi-byte character so searching the
+            // bytes finds a character boundary
+            let target = text.len() / n * i + text.len() % n * i / n;
+            let end = if i == n {
+                text.len()
+            } else {
+                text.as_bytes()[target..]
+                    .iter()
+                    .position(|&b| b == b'\n')
+                    .map_or(text.len(), |nl| target + nl + 1)
+            };
+            if end <= start.byte && !(i == n && shards.is_empty()) {
+                continue;
+            }
+            let mut shard = Chars::from_decoded(
+                TextChars {
+                    text: Arc::clone(&text),
+                    byte: start.byte,
+                    end,
+                },
+                start.byte,
+            );
+            shard.current = start;
+            shard.start = start;
+            shards.push(shard);
+
+            let lines = &text[start.byte..end];
+            start = Position {
+                loc: start.loc + lines.chars().count(),
+                byte: end,
+                line: start.line + lines.matches('\n').count(),
+                col: 1,
+                after_cr: false,
+            };
+        }
+        shards
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::Span;
+
+    fn spans(chars: &mut Chars) -> Vec<(char, Span)> {
+        let mut spans = Vec::new();
+        loop {
+            let start = chars.start_token();
+            let Some(c) = chars.next() else {
+                return spans;
+            };
+            spans.push((c, chars.end_token(start)));
+        }
+    }
+
+    #[rstest]
+    #[case("", 3, 1)]
+    #[case("one line", 4, 1)]
+    #[case("a\n£\n\nbc\n", 1, 1)]
+    #[case("a\n£\n\nbc\n", 3, 2)]
+    #[case("a\n£\n\nbc\n", 100, 4)]
+    fn shards_match_whole(
+        #[case] text: &str,
+        #[case] n: usize,
+        #[case] expected_shards: usize,
+    ) {
+        let mut shards = Chars::split_at_lines(text, n);
+        assert_eq!(shards.len(), expected_shards);
+        let sharded = shards.iter_mut().flat_map(spans).collect::<Vec<_>>();
+        assert_eq!(sharded, spans(&mut Chars::new(text)));
+    }
+
+    #[test]
+    fn huge_shard_count() {
+        let shards = Chars::split_at_lines("a\nb\n", usize::MAX);
+        assert_eq!(shards.len(), 2);
+    }
+
+    #[test]
+    fn stats_only_count_the_shard() {
+        let mut shards = Chars::split_at_lines("aaaa\nbbbb\ncccc\ndddd\n", 2);
+        let second = &mut shards[1];
+        assert_eq!((second.stats().consumed, second.stats().lines), (0, 1));
+        for _ in second.by_ref() {}
+        assert_eq!((second.stats().consumed, second.stats().lines), (5, 2));
+    }
+}
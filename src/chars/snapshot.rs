@@ -0,0 +1,195 @@
+use std::{fmt, sync::Arc};
+
+use super::{Chars, ColumnPolicy, LineTerminators, Position, TextChars};
+
+/// The position of a [Chars] iterator, saved with [Chars::snapshot] so
+/// lexing can be resumed later (possibly in another process) with
+/// [Chars::resume]. With the `serde` feature enabled it implements Serialize
+/// and Deserialize.
+///
+/// Only the cursor and the settings that affect positions (column policy and
+/// line terminators) are saved, the text is supplied again when resuming and
+/// checked against a digest of the original. Anything that was peeked is
+/// read from the text again, so nothing is lost by taking a snapshot while
+/// characters are buffered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    loc: usize,
+    byte: usize,
+    line: usize,
+    col: usize,
+    after_cr: bool,
+    last_consumed: Option<char>,
+    column_policy: ColumnPolicy,
+    line_terminators: LineTerminators,
+    digest: u64,
+}
+
+/// Reasons [Chars::resume] can fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResumeError {
+    /// The text isn't the text the snapshot was taken from
+    SourceChanged,
+    /// The snapshot's offset isn't a character boundary of the text, it
+    /// can't have been taken from this text
+    InvalidOffset(usize),
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for ResumeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResumeError::SourceChanged => {
+                write!(f, "the source has changed since the snapshot")
+            }
+            ResumeError::InvalidOffset(offset) => {
+                write!(f, "snapshot offset {offset} is not in the source")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ResumeError {}
+
+impl Chars {
+    /// Save the iterator's position so lexing can be resumed later with
+    /// [Chars::resume]. Returns None if the input isn't held in memory as
+    /// UTF-8 (see [Chars::fork])
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "let x;\nlet y;";
+    /// let mut chars = Chars::new(SRC);
+    /// for _ in (&mut chars).take(8) {}
+    /// assert_eq!(chars.peek(), Some('e'));
+    /// let snapshot = chars.snapshot().unwrap();
+    ///
+    /// let mut resumed = Chars::resume(SRC, &snapshot).unwrap();
+    /// assert_eq!(resumed.last_consumed(), Some('l'));
+    /// let start = resumed.start_token();
+    /// assert_eq!(resumed.next(), Some('e'));
+    /// let span = resumed.end_token(start);
+    /// assert_eq!(format!("{span}"), "line 2 column 2");
+    ///
+    /// assert_eq!(
+    ///     Chars::resume("let z;\nlet y;", &snapshot).err(),
+    ///     Some(ResumeError::SourceChanged)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn snapshot(&self) -> Option<Snapshot> {
+        let text = self.text.as_deref()?;
+        let Position {
+            loc,
+            byte,
+            line,
+            col,
+            after_cr,
+        } = self.current;
+        Some(Snapshot {
+            loc,
+            byte,
+            line,
+            col,
+            after_cr,
+            last_consumed: self.last_consumed,
+            column_policy: self.column_policy,
+            line_terminators: self.line_terminators,
+            digest: digest(text),
+        })
+    }
+
+    /// Resume iterating over `text` from a [Snapshot] taken by
+    /// [Chars::snapshot], with the column policy and line terminators of the
+    /// iterator it was taken from
+    ///
+    /// # Errors
+    /// If `text` isn't the text the snapshot was taken from
+    pub fn resume(
+        text: impl Into<Arc<str>>,
+        snapshot: &Snapshot,
+    ) -> Result<Chars, ResumeError> {
+        let text = text.into();
+        if digest(&text) != snapshot.digest {
+            return Err(ResumeError::SourceChanged);
+        }
+        if !text.is_char_boundary(snapshot.byte) {
+            return Err(ResumeError::InvalidOffset(snapshot.byte));
+        }
+        let mut chars = Chars::from_decoded(
+            TextChars {
+                text: Arc::clone(&text),
+                byte: snapshot.byte,
+                end: text.len(),
+            },
+            0,
+        );
+        chars.column_policy = snapshot.column_policy;
+        chars.line_terminators = snapshot.line_terminators;
+        // Find the line starts again so position_of works for the text
+        // before the snapshot
+        let mut position = chars.current;
+        for c in text[..snapshot.byte].chars() {
+            let line = position.line;
+            position.advance(
+                c,
+                c.len_utf8(),
+                chars.column_policy,
+                chars.line_terminators,
+            );
+            if position.line != line {
+                chars.line_starts.push(position.byte);
+            }
+        }
+        chars.text = Some(text);
+        chars.current = Position {
+            loc: snapshot.loc,
+            byte: snapshot.byte,
+            line: snapshot.line,
+            col: snapshot.col,
+            after_cr: snapshot.after_cr,
+        };
+        chars.start = chars.current;
+        chars.last_consumed = snapshot.last_consumed;
+        Ok(chars)
+    }
+}
+
+/// 64 bit FNV-1a hash of `text`, stable across processes and versions of
+/// Rust unlike the standard library's hashers
+fn digest(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn resumed_positions_match() {
+        const SRC: &str = "a\r\n£b\nc";
+        let mut chars =
+            Chars::new(SRC).with_line_terminators(LineTerminators::UNICODE);
+        for _ in (&mut chars).take(2) {}
+        let snapshot = chars.snapshot().unwrap();
+        let mut resumed = Chars::resume(SRC, &snapshot).unwrap();
+        let rest = |chars: &mut Chars| {
+            let mut spans = Vec::new();
+            loop {
+                let start = chars.start_token();
+                let Some(c) = chars.next() else {
+                    return spans;
+                };
+                spans.push((c, chars.end_token(start)));
+            }
+        };
+        assert_eq!(rest(&mut resumed), rest(&mut chars));
+        assert_eq!(resumed.position_of(7), chars.position_of(7));
+    }
+}
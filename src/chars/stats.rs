@@ -0,0 +1,55 @@
+use super::Chars;
+
+/// Counters describing how a [Chars] has been used, see [Chars::stats]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Stats {
+    /// Characters consumed
+    pub consumed: usize,
+    /// Lines the iterator has reached, including the one it started on. For
+    /// an iterator starting at the beginning of the input this is the line
+    /// it is on
+    pub lines: usize,
+    /// Furthest ahead of the head of the iterator that has been peeked, in
+    /// characters. Includes lookahead by checkpoints
+    pub peak_lookahead: usize,
+    /// Checkpoints created
+    pub checkpoints: usize,
+    /// Checkpoints committed
+    pub commits: usize,
+    /// Checkpoints aborted (or dropped)
+    pub aborts: usize,
+}
+
+impl Chars {
+    /// Counters for profiling and progress reporting. They are maintained as
+    /// the iterator is used so this is cheap to call.
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("ab\ncd");
+    /// let _ = chars.next();
+    /// let mut checkpoint = chars.checkpoint();
+    /// assert!(checkpoint.head_matches("b\nc"));
+    /// checkpoint.commit();
+    /// let mut checkpoint = chars.checkpoint();
+    /// let _ = checkpoint.next();
+    /// checkpoint.abort();
+    ///
+    /// let stats = chars.stats();
+    /// assert_eq!(stats.consumed, 4);
+    /// assert_eq!(stats.lines, 2);
+    /// assert_eq!(stats.peak_lookahead, 3);
+    /// assert_eq!((stats.checkpoints, stats.commits, stats.aborts), (2, 1, 1));
+    /// ```
+    #[must_use]
+    pub fn stats(&self) -> Stats {
+        // No checkpoint can be live while self is borrowed so every one that
+        // wasn't committed was aborted
+        Stats {
+            consumed: self.current.loc - self.start.loc,
+            lines: self.current.line - self.start.line + 1,
+            aborts: self.stats.checkpoints - self.stats.commits,
+            ..self.stats
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use std::fmt;
+
+use itertools::PeekingNext as _;
+
+use super::Chars;
+use crate::Span;
+
+/// Configures [Chars::scan_string]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StringOptions {
+    /// Character that opens and closes the literal
+    pub quote: char,
+    /// Character that introduces an escape sequence
+    pub escape: char,
+    /// Whether the literal may contain raw newlines. If not a newline ends
+    /// the literal as unterminated
+    pub multiline: bool,
+}
+
+impl Default for StringOptions {
+    fn default() -> Self {
+        Self {
+            quote: '"',
+            escape: '\\',
+            multiline: false,
+        }
+    }
+}
+
+/// A string literal scanned by [Chars::scan_string]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StringLiteral {
+    /// The contents with escapes decoded. Invalid escapes are left out
+    pub value: String,
+    /// The whole literal including the quotes
+    pub span: Span,
+    /// Each invalid escape sequence, the span starts at the escape character
+    pub errors: Vec<(Span, EscapeError)>,
+    /// False if the input (or line) ended before the closing quote
+    pub terminated: bool,
+}
+
+/// Reasons an escape sequence in a string literal can be invalid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EscapeError {
+    /// The character after the escape character isn't a known escape
+    Unknown(char),
+    /// `\x` wasn't followed by two hex digits no greater than `7f`
+    MalformedHex,
+    /// `\u` wasn't followed by 1 to 6 hex digits in braces
+    MalformedUnicode,
+    /// `\u{...}` named a surrogate or a value above `10ffff`
+    InvalidCodePoint(u32),
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for EscapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EscapeError::Unknown(c) => write!(f, "unknown escape {c:?}"),
+            EscapeError::MalformedHex => write!(
+                f,
+                "expected two hex digits no greater than 7f after \\x"
+            ),
+            EscapeError::MalformedUnicode => {
+                write!(f, "expected \\u{{...}} with 1 to 6 hex digits")
+            }
+            EscapeError::InvalidCodePoint(n) => {
+                write!(f, "{n:#x} is not a unicode scalar value")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EscapeError {}
+
+impl Chars {
+    /// Consume a quoted string literal, decoding escape sequences. Returns
+    /// None without consuming anything if the next character isn't the
+    /// opening quote.
+    ///
+    /// The supported escapes are `\n`, `\t`, `\r`, `\0`, `\xHH`, `\u{H...}`
+    /// and the escape character or either quote character escaping itself.
+    /// Invalid escapes are recorded with their spans and scanning continues,
+    /// an unterminated literal consumes up to the end of the input (or line).
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new(r#""tab\t\u{e9}\q" rest"#);
+    /// let literal = chars.scan_string(StringOptions::default()).unwrap();
+    /// assert_eq!(literal.value, "tab\té");
+    /// assert_eq!(format!("{:#}", literal.span), "line 1 column 1 to column 16");
+    /// assert!(literal.terminated);
+    /// let (span, error) = literal.errors[0];
+    /// assert_eq!(format!("{span:#}"), "line 1 column 13 to column 15");
+    /// assert_eq!(error, EscapeError::Unknown('q'));
+    /// assert_eq!(chars.next(), Some(' '));
+    /// ```
+    pub fn scan_string(
+        &mut self,
+        options: StringOptions,
+    ) -> Option<StringLiteral> {
+        if self.peek() != Some(options.quote) {
+            return None;
+        }
+        let start = self.start_token();
+        let _ = self.next();
+        let mut value = String::new();
+        let mut errors = Vec::new();
+        let mut terminated = false;
+        while let Some(c) = self.peek() {
+            if c == options.quote {
+                let _ = self.next();
+                terminated = true;
+                break;
+            }
+            if self.line_terminators.ends_line(c) && !options.multiline {
+                break;
+            }
+            if c == options.escape {
+                let escape = self.start_token();
+                let _ = self.next();
+                match self.escape(options) {
+                    Ok(c) => value.extend(c),
+                    Err(e) => errors.push((self.end_token(escape), e)),
+                }
+                continue;
+            }
+            let _ = self.next();
+            value.push(c);
+        }
+        Some(StringLiteral {
+            value,
+            span: self.end_token(start),
+            errors,
+            terminated,
+        })
+    }
+
+    /// The character produced by the escape sequence following an escape
+    /// character, None at the end of the input or the end of a line that ends
+    /// the literal
+    fn escape(
+        &mut self,
+        options: StringOptions,
+    ) -> Result<Option<char>, EscapeError> {
+        // Leave the line terminator so the literal ends there
+        let terminators = self.line_terminators;
+        if !options.multiline
+            && self.peek().is_some_and(|c| terminators.ends_line(c))
+        {
+            return Ok(None);
+        }
+        let Some(c) = self.next() else {
+            return Ok(None);
+        };
+        let escaped = match c {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            'x' => {
+                let digits = self.hex_digits(2);
+                u8::from_str_radix(&digits, 16)
+                    .ok()
+                    .filter(|n| digits.len() == 2 && n.is_ascii())
+                    .map(char::from)
+                    .ok_or(EscapeError::MalformedHex)?
+            }
+            'u' => {
+                if self.next_if_eq('{').is_none() {
+                    return Err(EscapeError::MalformedUnicode);
+                }
+                let digits = self.hex_digits(usize::MAX);
+                if self.next_if_eq('}').is_none()
+                    || digits.is_empty()
+                    || digits.len() > 6
+                {
+                    return Err(EscapeError::MalformedUnicode);
+                }
+                let n = u32::from_str_radix(&digits, 16)
+                    .map_err(|_| EscapeError::MalformedUnicode)?;
+                char::from_u32(n).ok_or(EscapeError::InvalidCodePoint(n))?
+            }
+            c if c == options.escape
+                || c == options.quote
+                || c == '"'
+                || c == '\'' =>
+            {
+                c
+            }
+            c => return Err(EscapeError::Unknown(c)),
+        };
+        Ok(Some(escaped))
+    }
+
+    /// Consume up to `max` hex digits
+    fn hex_digits(&mut self, max: usize) -> String {
+        self.peek_while(|c| c.is_ascii_hexdigit())
+            .take(max)
+            .collect()
+    }
+
+    fn next_if_eq(&mut self, expected: char) -> Option<char> {
+        self.peeking_next(|&c| c == expected)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::LineTerminators;
+
+    #[rstest]
+    #[case(r#""\x41\'\"\\""#, "A'\"\\", &[])]
+    #[case(r#""\x8f\x4""#, "", &[EscapeError::MalformedHex; 2])]
+    #[case(r#""\u{1F600}\u{}\u{d800}\u41""#, "😀41", &[
+        EscapeError::MalformedUnicode,
+        EscapeError::InvalidCodePoint(0xd800),
+        EscapeError::MalformedUnicode,
+    ])]
+    fn escapes(
+        #[case] source: &str,
+        #[case] value: &str,
+        #[case] errors: &[EscapeError],
+    ) {
+        let mut chars = Chars::new(source);
+        let literal = chars.scan_string(StringOptions::default()).unwrap();
+        assert_eq!(literal.value, value);
+        assert_eq!(
+            literal.errors.iter().map(|(_, e)| *e).collect::<Vec<_>>(),
+            errors
+        );
+        assert!(literal.terminated);
+    }
+
+    #[test]
+    fn unterminated_at_newline() {
+        let mut chars = Chars::new("'abc\ndef'");
+        let options = StringOptions {
+            quote: '\'',
+            ..StringOptions::default()
+        };
+        let literal = chars.scan_string(options).unwrap();
+        assert!(!literal.terminated);
+        assert_eq!(literal.value, "abc");
+        assert_eq!(chars.next(), Some('\n'));
+
+        let mut chars = Chars::new("'abc\ndef'");
+        let options = StringOptions {
+            multiline: true,
+            ..options
+        };
+        let literal = chars.scan_string(options).unwrap();
+        assert!(literal.terminated);
+        assert_eq!(literal.value, "abc\ndef");
+
+        let mut chars = Chars::new("\"ab\\\ncd\"");
+        let literal = chars.scan_string(StringOptions::default()).unwrap();
+        assert!(!literal.terminated);
+        assert_eq!(literal.value, "ab");
+        assert_eq!(chars.next(), Some('\n'));
+    }
+
+    #[test]
+    fn unterminated_at_unicode_line_terminator() {
+        for source in ["\"ab\u{2028}cd\"", "\"ab\\\rcd\""] {
+            let mut chars = Chars::new(source)
+                .with_line_terminators(LineTerminators::UNICODE);
+            let literal = chars.scan_string(StringOptions::default()).unwrap();
+            assert!(!literal.terminated);
+            assert_eq!(literal.value, "ab");
+            assert_eq!(chars.peek_line(), "");
+        }
+    }
+}
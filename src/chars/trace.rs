@@ -0,0 +1,94 @@
+use std::cell::RefCell;
+
+use super::Chars;
+use crate::Span;
+
+pub(super) type Hook = RefCell<Box<dyn FnMut(Trace) + Send>>;
+
+/// Event reported to the hook installed with [Chars::on_advance]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Trace {
+    /// A character was consumed, along with its span
+    Consumed(char, Span),
+    /// [Chars::start_token] was called, the span is empty and marks the
+    /// current position
+    StartToken(Span),
+    /// [Chars::end_token] produced this span
+    EndToken(Span),
+}
+
+impl Chars {
+    /// Install a hook that is called for each character consumed (including
+    /// by committed checkpoints) and each call to start_token and end_token,
+    /// so a lexer can be traced without modifying it. Peeking doesn't
+    /// trigger the hook. The hook must be Send so [Chars] stays Send.
+    ///
+    /// ```
+    /// # use span::*;
+    /// # use std::sync::{Arc, Mutex};
+    /// let log = Arc::new(Mutex::new(Vec::new()));
+    /// let mut chars = Chars::new("ab").on_advance({
+    ///     let log = Arc::clone(&log);
+    ///     move |trace| log.lock().unwrap().push(trace)
+    /// });
+    /// let start = chars.start_token();
+    /// let _ = chars.next();
+    /// let span = chars.end_token(start);
+    /// let _ = chars.peek();
+    ///
+    /// let log = log.lock().unwrap();
+    /// assert_eq!(log.len(), 3);
+    /// assert!(matches!(log[0], Trace::StartToken(_)));
+    /// assert_eq!(log[1], Trace::Consumed('a', span));
+    /// assert_eq!(log[2], Trace::EndToken(span));
+    /// ```
+    #[must_use]
+    pub fn on_advance(
+        mut self,
+        hook: impl FnMut(Trace) + Send + 'static,
+    ) -> Self {
+        self.hook = Some(RefCell::new(Box::new(hook)));
+        self
+    }
+
+    pub(super) fn trace(&self, event: impl FnOnce() -> Trace) {
+        if let Some(hook) = &self.hook {
+            hook.borrow_mut()(event());
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn every_consumed_char_is_reported() {
+        let consumed = Arc::new(AtomicUsize::new(0));
+        let mut chars = Chars::new("12\n345").on_advance({
+            let consumed = Arc::clone(&consumed);
+            move |trace| {
+                if let Trace::Consumed(..) = trace {
+                    let _ = consumed.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        });
+        let _ = chars.next();
+        assert_eq!(chars.advance_by(2), Ok(()));
+        let mut checkpoint = chars.checkpoint();
+        let _ = checkpoint.nth(1);
+        checkpoint.commit();
+        let mut checkpoint = chars.checkpoint();
+        let _ = checkpoint.next();
+        checkpoint.abort();
+        assert_eq!(consumed.load(Ordering::Relaxed), 5);
+    }
+}
@@ -0,0 +1,131 @@
+use super::Chars;
+use crate::Span;
+
+/// A token along with the whitespace and comments around it, produced by
+/// [Chars::lex_with_trivia]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WithTrivia<T> {
+    /// The token
+    pub token: T,
+    /// Trivia between the previous token's trailing trivia and this token
+    pub leading: Vec<Span>,
+    /// Trivia after the token that starts on the line the token ends on
+    pub trailing: Vec<Span>,
+}
+
+impl Chars {
+    /// Lex the input with `token`, attaching the trivia (whitespace,
+    /// comments...) consumed by `trivia` to the tokens either side of it, so
+    /// a formatter can reproduce or move it along with the tokens.
+    ///
+    /// `trivia` should consume one piece of trivia and return its span, or
+    /// return None without consuming anything. `token` should consume one
+    /// token, or return None if there isn't one. Trivia is trailing if it
+    /// starts on the line the token ends on, so a piece of trivia that
+    /// includes the newline ends the trailing trivia; everything else is
+    /// leading trivia of the next token.
+    ///
+    /// Lexing stops when neither function consumes anything, so `peek` can
+    /// be used to check whether the whole input was lexed. Trivia after the
+    /// last token is returned separately.
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "// header\nx = 1 // one\n";
+    /// let mut chars = Chars::new(SRC);
+    /// let (tokens, rest) = chars.lex_with_trivia(
+    ///     |chars| {
+    ///         chars.skip_line_comment("//").or_else(|| {
+    ///             let start = chars.start_token();
+    ///             let ws = chars.peek_while(char::is_whitespace).count();
+    ///             (ws > 0).then(|| chars.end_token(start))
+    ///         })
+    ///     },
+    ///     |chars| {
+    ///         let start = chars.start_token();
+    ///         let len = chars.peek_while(|c| !c.is_whitespace()).count();
+    ///         (len > 0).then(|| chars.end_token(start))
+    ///     },
+    /// );
+    /// assert_eq!(chars.peek(), None);
+    /// assert_eq!(tokens.len(), 3);
+    /// assert_eq!(tokens[0].leading, [
+    ///     testing::span_of(SRC, "// header", 0),
+    ///     testing::span_of(SRC, "\n", 0),
+    /// ]);
+    /// assert_eq!(tokens[2].token, testing::span_of(SRC, "1", 0));
+    /// assert_eq!(tokens[2].trailing, [
+    ///     testing::span_of(SRC, " ", 3),
+    ///     testing::span_of(SRC, "// one", 0),
+    ///     testing::span_of(SRC, "\n", 1),
+    /// ]);
+    /// assert!(rest.is_empty());
+    /// ```
+    pub fn lex_with_trivia<T>(
+        &mut self,
+        mut trivia: impl FnMut(&mut Chars) -> Option<Span>,
+        mut token: impl FnMut(&mut Chars) -> Option<T>,
+    ) -> (Vec<WithTrivia<T>>, Vec<Span>) {
+        let mut tokens = Vec::new();
+        let mut leading = Vec::new();
+        loop {
+            leading.extend(std::iter::from_fn(|| trivia(self)));
+            let Some(token) = token(self) else {
+                return (tokens, leading);
+            };
+            let line = self.current.line;
+            let mut trailing = Vec::new();
+            while self.current.line == line
+                && let Some(span) = trivia(self)
+            {
+                trailing.push(span);
+            }
+            tokens.push(WithTrivia {
+                token,
+                leading: std::mem::take(&mut leading),
+                trailing,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use itertools::PeekingNext as _;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::testing::span_of;
+
+    #[test]
+    fn trailing_stops_at_newline() {
+        const SRC: &str = "a b\n\n  c ";
+        let mut chars = Chars::new(SRC);
+        let (tokens, rest) = chars.lex_with_trivia(
+            |chars| {
+                let start = chars.start_token();
+                let _ = chars.peeking_next(|c| c.is_whitespace())?;
+                Some(chars.end_token(start))
+            },
+            |chars| chars.scan_identifier(char::is_alphabetic, |_| false),
+        );
+        let trivia = |token: &WithTrivia<(String, Span)>| {
+            (
+                token.token.0.clone(),
+                token.leading.len(),
+                token.trailing.len(),
+            )
+        };
+        assert_eq!(
+            tokens.iter().map(trivia).collect::<Vec<_>>(),
+            [
+                (String::from("a"), 0, 1),
+                (String::from("b"), 0, 1),
+                (String::from("c"), 3, 1),
+            ]
+        );
+        assert_eq!(tokens[2].leading[0], span_of(SRC, "\n", 1));
+        assert!(rest.is_empty());
+    }
+}
@@ -0,0 +1,326 @@
+use std::fmt;
+
+use crate::{Span, snippet::write_snippet};
+
+/// See [Span::with_source_name]
+#[derive(Debug, Clone, Copy)]
+pub struct WithSourceName<'a> {
+    span: Span,
+    name: &'a str,
+}
+
+impl<'a> WithSourceName<'a> {
+    pub(crate) fn new(span: Span, name: &'a str) -> Self {
+        Self { span, name }
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for WithSourceName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.name)?;
+        if f.alternate() {
+            write!(f, "{:#}", self.span)
+        } else {
+            write!(f, "{}", self.span)
+        }
+    }
+}
+
+/// See [Span::display_with] and
+/// [MultiSpan::display_with](crate::MultiSpan::display_with)
+#[derive(Debug, Clone, Copy)]
+pub struct WithSource<'a> {
+    span: Span,
+    secondary: &'a [Span],
+    source: &'a str,
+}
+
+impl<'a> WithSource<'a> {
+    pub(crate) fn new(span: Span, source: &'a str) -> Self {
+        Self {
+            span,
+            secondary: &[],
+            source,
+        }
+    }
+
+    pub(crate) fn with_secondary(self, secondary: &'a [Span]) -> Self {
+        Self { secondary, ..self }
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for WithSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            writeln!(f, "{:#}", self.span)?;
+        } else {
+            writeln!(f, "{}", self.span)?;
+        }
+        write_snippet(f, self.source, self.span)?;
+        for span in self.secondary {
+            writeln!(f, "{span:#}")?;
+            write_snippet(f, self.source, *span)?;
+        }
+        Ok(())
+    }
+}
+
+/// See [Span::summarize]
+#[derive(Debug, Clone)]
+pub struct Locations {
+    shown: Vec<Span>,
+    more: usize,
+}
+
+impl Locations {
+    pub(crate) fn new(spans: &[Span], max: usize) -> Self {
+        let mut spans = spans.to_vec();
+        spans.sort_by_key(|span| {
+            (span.is_unknown(), span.absolute.map(|a| (a.start, a.end)))
+        });
+        spans.dedup();
+        let more = spans.len().saturating_sub(max);
+        spans.truncate(max);
+        Self { shown: spans, more }
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for Locations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, span) in self.shown.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            if f.alternate() {
+                write!(f, "{span:#}")?;
+            } else {
+                write!(f, "{span}")?;
+            }
+        }
+        match self.more {
+            0 => Ok(()),
+            1 => write!(f, " and 1 more location"),
+            more => write!(f, " and {more} more locations"),
+        }
+    }
+}
+
+/// See [Span::display_visual]
+#[derive(Debug, Clone, Copy)]
+pub struct Visual(Span);
+
+impl Visual {
+    pub(crate) fn new(span: Span) -> Self {
+        Self(span)
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for Visual {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_span(f, self.0, &Wording::ENGLISH)
+    }
+}
+
+/// See [Span::display_bytes]
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayBytes(Span);
+
+impl DisplayBytes {
+    pub(crate) fn new(span: Span) -> Self {
+        Self(span)
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for DisplayBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.absolute {
+            Some(absolute) => {
+                write!(
+                    f,
+                    "bytes {}..{}",
+                    absolute.start_byte, absolute.end_byte
+                )
+            }
+            None => write!(f, "???"),
+        }
+    }
+}
+
+/// See [Span::hyperlink]
+#[derive(Debug, Clone, Copy)]
+pub struct Hyperlink<'a> {
+    span: Span,
+    path: &'a str,
+    enabled: bool,
+}
+
+impl<'a> Hyperlink<'a> {
+    pub(crate) fn new(span: Span, path: &'a str) -> Self {
+        Self {
+            span,
+            path,
+            enabled: Self::detect(),
+        }
+    }
+
+    /// Override whether the OSC 8 escape sequences are written, e.g. to
+    /// disable them when writing to a file or to follow a `--hyperlinks`
+    /// command line flag
+    #[must_use]
+    pub fn enabled(self, enabled: bool) -> Self {
+        Self { enabled, ..self }
+    }
+
+    /// Guess whether the terminal supports OSC 8 hyperlinks from the
+    /// environment. `FORCE_HYPERLINK=1` or `FORCE_HYPERLINK=0` overrides the
+    /// guess. This doesn't check whether the output is actually a terminal
+    #[must_use]
+    pub fn detect() -> bool {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        if let Ok(force) = std::env::var("FORCE_HYPERLINK") {
+            return !force.is_empty() && force != "0";
+        }
+        let term = var("TERM");
+        if term == "dumb" {
+            return false;
+        }
+        ["WT_SESSION", "KONSOLE_VERSION", "DOMTERM"]
+            .into_iter()
+            .any(|name| std::env::var_os(name).is_some())
+            || ["iTerm.app", "WezTerm", "vscode", "ghostty", "Hyper"]
+                .contains(&var("TERM_PROGRAM").as_str())
+            || var("VTE_VERSION")
+                .parse::<u32>()
+                .is_ok_and(|version| version >= 5000)
+            || ["kitty", "alacritty", "foot"]
+                .into_iter()
+                .any(|name| term.contains(name))
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for Hyperlink<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = WithSourceName::new(self.span, self.path);
+        let line = self.span.start_line().filter(|_| self.enabled);
+        let Some(line) = line else {
+            return if f.alternate() {
+                write!(f, "{text:#}")
+            } else {
+                write!(f, "{text}")
+            };
+        };
+        write!(f, "\x1b]8;;file://")?;
+        for byte in self.path.bytes() {
+            if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+                write!(f, "{}", char::from(byte))?;
+            } else {
+                write!(f, "%{byte:02X}")?;
+            }
+        }
+        write!(f, "#{line}\x1b\\")?;
+        if f.alternate() {
+            write!(f, "{text:#}")?;
+        } else {
+            write!(f, "{text}")?;
+        }
+        write!(f, "\x1b]8;;\x1b\\")
+    }
+}
+
+/// The words used to display a [Span], see [Span::localized]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Wording<'a> {
+    /// Label placed before line numbers
+    pub line: &'a str,
+    /// Label placed before column numbers
+    pub column: &'a str,
+    /// Separates the start of a range from its end
+    pub to: &'a str,
+    /// Printed in place of an unknown span
+    pub unknown: &'a str,
+}
+
+impl Wording<'_> {
+    /// The wording used by [Span]'s Display impl
+    pub const ENGLISH: Wording<'static> = Wording {
+        line: "line",
+        column: "column",
+        to: "to",
+        unknown: "???",
+    };
+}
+
+impl Default for Wording<'_> {
+    fn default() -> Self {
+        Wording::ENGLISH
+    }
+}
+
+/// See [Span::localized]
+#[derive(Debug, Clone, Copy)]
+pub struct Localized<'a> {
+    span: Span,
+    wording: Wording<'a>,
+}
+
+impl<'a> Localized<'a> {
+    pub(crate) fn new(span: Span, wording: Wording<'a>) -> Self {
+        Self { span, wording }
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for Localized<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_span(f, self.span, &self.wording)
+    }
+}
+
+/// Implementation of [Span]'s Display using the words from `wording`
+#[cfg_attr(coverage, coverage(off))]
+pub(crate) fn write_span(
+    f: &mut fmt::Formatter<'_>,
+    span: Span,
+    wording: &Wording<'_>,
+) -> fmt::Result {
+    let Wording {
+        line,
+        column,
+        to,
+        unknown,
+    } = wording;
+    if span.is_unknown() {
+        return write!(f, "{unknown}");
+    }
+
+    let start = span.relative.start;
+    let end = span.relative.end;
+    write!(f, "{line} {} {column} {}", start.line, start.column)?;
+
+    // If the span is empty stop at printing the start character location
+    if start == end {
+        return Ok(());
+    }
+
+    // As above if the span is only 1 character wide
+    if start.line == end.line && start.column + 1 == end.column {
+        return Ok(());
+    }
+
+    // If # is specified and the span is more than 1 character wide print
+    // the end
+    if f.alternate() {
+        write!(f, " {to}")?;
+        if start.line != end.line {
+            write!(f, " {line} {}", end.line)?;
+        }
+        write!(f, " {column} {}", end.column)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,235 @@
+use std::fmt;
+
+use crate::{
+    AbsoluteSpan, InvalidSpan, LineAndColumn, Provenance, RelativeSpan, Span,
+};
+
+/// Bumped whenever the layout produced by [Span::to_bytes] changes
+const VERSION: u8 = 2;
+
+const FIELD: usize = size_of::<u64>();
+
+/// Errors produced by [Span::from_bytes]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input wasn't exactly [Span::ENCODED_LEN] bytes long
+    WrongLength(usize),
+    /// The input was encoded by an incompatible version of this crate
+    UnsupportedVersion(u8),
+    /// The byte marking whether the span is known was neither 0 nor 1, the
+    /// input is corrupt
+    InvalidFlag(u8),
+    /// The input decoded to an inconsistent span
+    Invalid(InvalidSpan),
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::WrongLength(len) => write!(
+                f,
+                "expected {} bytes for an encoded span, found {len}",
+                Span::ENCODED_LEN
+            ),
+            DecodeError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported span encoding version {version} (expected \
+                 {VERSION})"
+            ),
+            DecodeError::InvalidFlag(flag) => {
+                write!(f, "invalid span encoding flag {flag}")
+            }
+            DecodeError::Invalid(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl Span {
+    /// Length of the encoding produced by [Span::to_bytes]
+    pub const ENCODED_LEN: usize = 2 + 8 * FIELD;
+
+    /// Stable fixed size binary encoding, suitable for caching spans between
+    /// runs. The layout is:
+    ///
+    /// * A version byte, checked by [Span::from_bytes]
+    /// * A byte that is 0 for [Span::UNKNOWN] and 1 otherwise
+    /// * The absolute start and end in characters then in bytes, followed by
+    ///   the start line and column and the end line and column, each as a
+    ///   little endian u64. These are all 0 for [Span::UNKNOWN]
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = &mut Chars::new("123\n456");
+    /// let start = chars.start_token();
+    /// for _ in chars.take(5) {}
+    /// let span = chars.end_token(start);
+    /// let bytes = span.to_bytes();
+    /// assert_eq!(bytes.len(), Span::ENCODED_LEN);
+    /// assert_eq!(Span::from_bytes(&bytes), Ok(span));
+    /// assert_eq!(
+    ///     Span::from_bytes(&Span::UNKNOWN.to_bytes()),
+    ///     Ok(Span::UNKNOWN)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; Span::ENCODED_LEN] {
+        let mut bytes = [0; Span::ENCODED_LEN];
+        bytes[0] = VERSION;
+        let Some(AbsoluteSpan {
+            start,
+            end,
+            start_byte,
+            end_byte,
+        }) = self.absolute
+        else {
+            return bytes;
+        };
+        bytes[1] = 1;
+        let RelativeSpan {
+            start: first,
+            end: last,
+        } = self.relative;
+        let fields = [
+            start,
+            end,
+            start_byte,
+            end_byte,
+            first.line,
+            first.column,
+            last.line,
+            last.column,
+        ];
+        for (chunk, field) in bytes[2..].chunks_exact_mut(FIELD).zip(fields) {
+            chunk.copy_from_slice(&(field as u64).to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode a span encoded by [Span::to_bytes]
+    ///
+    /// # Errors
+    /// If the input was produced by a different version of the encoding, is
+    /// the wrong length, is corrupt or doesn't describe a valid span. The
+    /// version is checked first since other versions may have other lengths
+    pub fn from_bytes(bytes: &[u8]) -> Result<Span, DecodeError> {
+        match bytes.first() {
+            Some(&VERSION) => {}
+            Some(&version) => {
+                return Err(DecodeError::UnsupportedVersion(version));
+            }
+            None => return Err(DecodeError::WrongLength(0)),
+        }
+        if bytes.len() != Span::ENCODED_LEN {
+            return Err(DecodeError::WrongLength(bytes.len()));
+        }
+        match bytes[1] {
+            0 => return Ok(Span::UNKNOWN),
+            1 => {}
+            flag => return Err(DecodeError::InvalidFlag(flag)),
+        }
+        let mut fields = bytes[2..].chunks_exact(FIELD).map(|chunk| {
+            let mut field = [0; FIELD];
+            field.copy_from_slice(chunk);
+            usize::try_from(u64::from_le_bytes(field)).unwrap_or(usize::MAX)
+        });
+        let mut next = || fields.next().unwrap_or_default();
+        let span = Span {
+            absolute: Some(AbsoluteSpan {
+                start: next(),
+                end: next(),
+                start_byte: next(),
+                end_byte: next(),
+            }),
+            relative: RelativeSpan {
+                start: LineAndColumn {
+                    line: next(),
+                    column: next(),
+                },
+                end: LineAndColumn {
+                    line: next(),
+                    column: next(),
+                },
+            },
+            provenance: Provenance::NONE,
+        };
+        span.validate().map_err(DecodeError::Invalid)?;
+        Ok(span)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn layout() {
+        let span = Span {
+            absolute: Some(AbsoluteSpan {
+                start: 1,
+                end: 9,
+                start_byte: 2,
+                end_byte: 12,
+            }),
+            relative: RelativeSpan {
+                start: LineAndColumn { line: 1, column: 2 },
+                end: LineAndColumn { line: 3, column: 4 },
+            },
+            provenance: Provenance::NONE,
+        };
+        let mut expected = vec![VERSION, 1];
+        for field in [1u64, 9, 2, 12, 1, 2, 3, 4] {
+            expected.extend(field.to_le_bytes());
+        }
+        assert_eq!(span.to_bytes().as_slice(), expected);
+    }
+
+    #[test]
+    fn wrong_length() {
+        assert_eq!(
+            Span::from_bytes(&[VERSION]),
+            Err(DecodeError::WrongLength(1))
+        );
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let mut bytes = Span::UNKNOWN.to_bytes();
+        bytes[0] = VERSION + 1;
+        assert_eq!(
+            Span::from_bytes(&bytes),
+            Err(DecodeError::UnsupportedVersion(VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn version_checked_before_length() {
+        assert_eq!(
+            Span::from_bytes(&[VERSION + 1, 0]),
+            Err(DecodeError::UnsupportedVersion(VERSION + 1))
+        );
+        assert_eq!(Span::from_bytes(&[]), Err(DecodeError::WrongLength(0)));
+    }
+
+    #[test]
+    fn invalid_flag() {
+        let mut bytes = Span::UNKNOWN.to_bytes();
+        bytes[1] = 2;
+        assert_eq!(Span::from_bytes(&bytes), Err(DecodeError::InvalidFlag(2)));
+    }
+
+    #[test]
+    fn invalid() {
+        let mut bytes = Span::UNKNOWN.to_bytes();
+        bytes[1] = 1;
+        assert_eq!(
+            Span::from_bytes(&bytes),
+            Err(DecodeError::Invalid(InvalidSpan::ZeroLineOrColumn))
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    AbsoluteSpan, InvalidSpan, LineAndColumn, Provenance, RelativeSpan, Span,
+};
+
+/// A [Span] with a fixed C layout, for passing spans across an FFI boundary.
+/// The fields are the same as the serialized form of [Span].
+///
+/// The type is suitable for cbindgen, which generates
+///
+/// ```c
+/// typedef struct SpanRaw {
+///   uintptr_t start;
+///   uintptr_t end;
+///   uintptr_t start_byte;
+///   uintptr_t end_byte;
+///   uintptr_t start_line;
+///   uintptr_t start_col;
+///   uintptr_t end_line;
+///   uintptr_t end_col;
+/// } SpanRaw;
+/// ```
+///
+/// Lines and columns are 1 indexed so [Span::UNKNOWN] is represented by
+/// [SpanRaw::UNKNOWN], all zeroes, which C code can produce with
+/// `SpanRaw span = {0};`.
+///
+/// ```
+/// # use span::*;
+/// let mut chars = &mut Chars::new("123\n456");
+/// let start = chars.start_token();
+/// for _ in chars.take(5) {}
+/// let span = chars.end_token(start);
+/// let raw = SpanRaw::from(span);
+/// assert_eq!((raw.start_byte, raw.end_line, raw.end_col), (0, 2, 2));
+/// assert_eq!(Span::try_from(raw), Ok(span));
+/// assert_eq!(SpanRaw::from(Span::UNKNOWN), SpanRaw::UNKNOWN);
+/// assert_eq!(Span::try_from(SpanRaw::UNKNOWN), Ok(Span::UNKNOWN));
+/// let zero_line = SpanRaw { end_line: 0, ..raw };
+/// assert_eq!(Span::try_from(zero_line), Err(InvalidSpan::ZeroLineOrColumn));
+/// ```
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SpanRaw {
+    /// Character offset of the start of the span
+    pub start: usize,
+    /// Character offset of the (exclusive) end of the span
+    pub end: usize,
+    /// Byte offset of the start of the span
+    pub start_byte: usize,
+    /// Byte offset of the (exclusive) end of the span
+    pub end_byte: usize,
+    /// Line of the start of the span (1 indexed)
+    pub start_line: usize,
+    /// Column of the start of the span (1 indexed)
+    pub start_col: usize,
+    /// Line of the (exclusive) end of the span (1 indexed)
+    pub end_line: usize,
+    /// Column of the (exclusive) end of the span (1 indexed)
+    pub end_col: usize,
+}
+
+impl SpanRaw {
+    /// Representation of [Span::UNKNOWN]
+    pub const UNKNOWN: SpanRaw = SpanRaw {
+        start: 0,
+        end: 0,
+        start_byte: 0,
+        end_byte: 0,
+        start_line: 0,
+        start_col: 0,
+        end_line: 0,
+        end_col: 0,
+    };
+}
+
+impl From<Span> for SpanRaw {
+    fn from(span: Span) -> Self {
+        let Some(AbsoluteSpan {
+            start,
+            end,
+            start_byte,
+            end_byte,
+        }) = span.absolute
+        else {
+            return SpanRaw::UNKNOWN;
+        };
+        let RelativeSpan {
+            start: from,
+            end: to,
+        } = span.relative;
+        SpanRaw {
+            start,
+            end,
+            start_byte,
+            end_byte,
+            start_line: from.line,
+            start_col: from.column,
+            end_line: to.line,
+            end_col: to.column,
+        }
+    }
+}
+
+/// Fails if the fields are inconsistent (other than all being zero), see
+/// [InvalidSpan]
+impl TryFrom<SpanRaw> for Span {
+    type Error = InvalidSpan;
+
+    fn try_from(raw: SpanRaw) -> Result<Self, Self::Error> {
+        if raw == SpanRaw::UNKNOWN {
+            return Ok(Span::UNKNOWN);
+        }
+        let span = Span {
+            absolute: Some(AbsoluteSpan {
+                start: raw.start,
+                end: raw.end,
+                start_byte: raw.start_byte,
+                end_byte: raw.end_byte,
+            }),
+            relative: RelativeSpan {
+                start: LineAndColumn {
+                    line: raw.start_line,
+                    column: raw.start_col,
+                },
+                end: LineAndColumn {
+                    line: raw.end_line,
+                    column: raw.end_col,
+                },
+            },
+            provenance: Provenance::NONE,
+        };
+        span.validate()?;
+        Ok(span)
+    }
+}
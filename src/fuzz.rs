@@ -0,0 +1,81 @@
+#[cfg(feature = "arbitrary")]
+use arbitrary::{Arbitrary, Unstructured};
+
+use crate::{Chars, Span};
+
+/// Source text paired with a span that lies within it, for property testing.
+/// Generated with `arbitrary` when the `arbitrary` feature is enabled, or
+/// with the strategies in the `strategy` module when the `proptest`
+/// feature is enabled
+#[derive(Debug, Clone)]
+pub struct SourceAndSpan {
+    /// Arbitrary source text
+    pub source: String,
+    /// Span covering an arbitrary range of `source`
+    pub span: Span,
+}
+
+impl SourceAndSpan {
+    /// Pair `source` with the span from character `start` up to character
+    /// `end`, produced by running [Chars] over it so the span is always
+    /// internally consistent
+    pub(crate) fn new(source: String, start: usize, end: usize) -> Self {
+        let mut chars = Chars::new(source.clone());
+        for _ in (&mut chars).take(start) {}
+        let token = chars.start_token();
+        for _ in (&mut chars).take(end.saturating_sub(start)) {}
+        let span = chars.end_token(token);
+        SourceAndSpan { source, span }
+    }
+}
+
+/// The span covers an arbitrary range of arbitrary source text
+///
+/// ```
+/// # use span::*;
+/// use arbitrary::{Arbitrary as _, Unstructured};
+///
+/// let mut u = Unstructured::new(b"some random bytes from a fuzzer");
+/// let SourceAndSpan { source, span } =
+///     SourceAndSpan::arbitrary(&mut u).unwrap();
+/// let end = span.start().unwrap() + span.len_chars().unwrap();
+/// assert!(end <= source.chars().count());
+/// ```
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for SourceAndSpan {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let source = String::arbitrary(u)?;
+        let len = source.chars().count();
+        let start = u.int_in_range(0..=len)?;
+        let end = u.int_in_range(start..=len)?;
+        Ok(SourceAndSpan::new(source, start, end))
+    }
+}
+
+/// Spans are generated by running [Chars] over arbitrary source text so they
+/// are always internally consistent. Occasionally produces [Span::UNKNOWN]
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for Span {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        if u.ratio(1, 16)? {
+            return Ok(Span::UNKNOWN);
+        }
+        Ok(SourceAndSpan::arbitrary(u)?.span)
+    }
+}
+
+#[cfg(all(test, feature = "arbitrary"))]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spans_are_valid() {
+        let data = (0..=u8::MAX).cycle().take(4096).collect::<Vec<_>>();
+        let mut u = Unstructured::new(&data);
+        while !u.is_empty() {
+            let span = Span::arbitrary(&mut u).unwrap();
+            assert_eq!(span.validate(), Ok(()));
+        }
+    }
+}
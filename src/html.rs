@@ -0,0 +1,131 @@
+//! Rendering source as HTML with regions identified by spans wrapped in
+//! `<span class="...">` elements, e.g. for syntax highlighting in generated
+//! documentation
+
+use std::{cmp::Reverse, fmt::Write as _};
+
+use crate::Span;
+
+/// Render `source` as escaped HTML with each region wrapped in a
+/// `<span class="...">` element carrying its class.
+///
+/// Regions that contain each other are nested. Regions that partly overlap
+/// can't be nested, so the one that starts later is closed where the other
+/// ends and reopened straight after. Regions with the same start are nested
+/// longest first, then in the order they were given. Empty spans,
+/// Span::UNKNOWN and spans that don't fit `source` are ignored.
+///
+/// ```
+/// # use span::*;
+/// const SRC: &str = "if a < b {}";
+/// let html = html::render(SRC, &[
+///     (testing::span_of(SRC, "a < b", 0), "condition"),
+///     (testing::span_of(SRC, "if", 0), "keyword"),
+///     (testing::span_of(SRC, "<", 0), "operator"),
+/// ]);
+/// assert_eq!(
+///     html,
+///     "<span class=\"keyword\">if</span> <span class=\"condition\">a \
+///      <span class=\"operator\">&lt;</span> b</span> {}"
+/// );
+/// ```
+#[must_use]
+pub fn render(source: &str, regions: &[(Span, &str)]) -> String {
+    let mut regions = regions
+        .iter()
+        .filter(|(span, _)| span.is_valid_for(source))
+        .filter_map(|(span, class)| {
+            let absolute = span.absolute?;
+            (absolute.start_byte < absolute.end_byte).then_some((
+                absolute.start_byte,
+                absolute.end_byte,
+                *class,
+            ))
+        })
+        .collect::<Vec<_>>();
+    regions.sort_by_key(|&(start, end, _)| (start, Reverse(end)));
+    let mut boundaries = regions
+        .iter()
+        .flat_map(|&(start, end, _)| [start, end])
+        .collect::<Vec<_>>();
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut html = String::with_capacity(source.len());
+    let mut open = Vec::<usize>::new();
+    let mut next = 0;
+    let mut written = 0;
+    for boundary in boundaries {
+        escape(&mut html, &source[written..boundary]);
+        written = boundary;
+        if let Some(deepest) =
+            open.iter().position(|&i| regions[i].1 == boundary)
+        {
+            let closed = open.split_off(deepest);
+            html.extend(closed.iter().map(|_| "</span>"));
+            for i in closed {
+                if regions[i].1 > boundary {
+                    open_span(&mut html, regions[i].2);
+                    open.push(i);
+                }
+            }
+        }
+        while let Some(&(start, _, class)) = regions.get(next)
+            && start == boundary
+        {
+            open_span(&mut html, class);
+            open.push(next);
+            next += 1;
+        }
+    }
+    escape(&mut html, &source[written..]);
+    html
+}
+
+fn open_span(html: &mut String, class: &str) {
+    html.push_str("<span class=\"");
+    escape(html, class);
+    html.push_str("\">");
+}
+
+fn escape(html: &mut String, text: &str) {
+    for c in text.chars() {
+        let _ = match c {
+            '&' => html.write_str("&amp;"),
+            '<' => html.write_str("&lt;"),
+            '>' => html.write_str("&gt;"),
+            '"' => html.write_str("&quot;"),
+            '\'' => html.write_str("&#39;"),
+            c => html.write_char(c),
+        };
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::testing::span_of;
+
+    const SRC: &str = "abcdef";
+
+    #[rstest]
+    #[case(&[("abcd", "x"), ("cdef", "y")], "<span class=\"x\">ab<span class=\"y\">cd</span></span><span class=\"y\">ef</span>")]
+    #[case(&[("abc", "x"), ("abc", "y")], "<span class=\"x\"><span class=\"y\">abc</span></span>def")]
+    #[case(&[("ab", "x"), ("cd", "y")], "<span class=\"x\">ab</span><span class=\"y\">cd</span>ef")]
+    #[case(&[("abcdef", "\"&'")], "<span class=\"&quot;&amp;&#39;\">abcdef</span>")]
+    fn overlapping_regions(
+        #[case] regions: &[(&str, &str)],
+        #[case] expected: &str,
+    ) {
+        let regions = regions
+            .iter()
+            .map(|&(needle, class)| (span_of(SRC, needle, 0), class))
+            .chain([(Span::UNKNOWN, "unknown")])
+            .collect::<Vec<_>>();
+        assert_eq!(render(SRC, &regions), expected);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{LineAndColumn, Span};
+
+impl Span {
+    /// Span of the character `serde_json` reported `error` at, where
+    /// `source` is the text that was being parsed. Returns None for errors
+    /// without a position (e.g. I/O errors) or if the position is outside
+    /// `source`. Errors caused by the input ending early get an empty span at
+    /// the end of `source`.
+    ///
+    /// `serde_json` counts columns in bytes, up to and including the
+    /// character that caused the error, so they don't line up with
+    /// [Span::from_line_col] for non-ASCII text or errors at a newline.
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "{\n  \"é\": tru\n}";
+    /// let error = serde_json::from_str::<serde_json::Value>(SRC).unwrap_err();
+    /// let span = Span::from_serde_json_error(SRC, &error).unwrap();
+    /// assert_eq!(format!("{span:#}"), "line 2 column 11");
+    /// ```
+    #[must_use]
+    pub fn from_serde_json_error(
+        source: &str,
+        error: &serde_json::Error,
+    ) -> Option<Span> {
+        if error.line() == 0 {
+            return None;
+        }
+        if error.is_eof() {
+            return Some(Span::char_at(source, source.len()));
+        }
+        let line_start = LineAndColumn {
+            line: error.line(),
+            column: 1,
+        }
+        .byte_offset_in(source)?;
+        let line_len = source[line_start..]
+            .find('\n')
+            .unwrap_or(source.len() - line_start);
+        // Column 0 is the newline ending the previous line
+        let mut start =
+            (line_start + error.column().min(line_len + 1)).saturating_sub(1);
+        while !source.is_char_boundary(start) {
+            start -= 1;
+        }
+        Some(Span::char_at(source, start))
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("[1, 2", 1, 6)]
+    #[case("[1, x]", 1, 5)]
+    #[case("\"\u{e9}\u{1}\"", 1, 3)]
+    #[case("[\"a\nb\"]", 1, 4)]
+    fn error_spans(
+        #[case] source: &str,
+        #[case] line: usize,
+        #[case] column: usize,
+    ) {
+        let error =
+            serde_json::from_str::<serde_json::Value>(source).unwrap_err();
+        assert_eq!(
+            Span::from_serde_json_error(source, &error),
+            Span::from_line_col(source, line, column)
+        );
+    }
+}
@@ -18,14 +18,75 @@
 #![allow(clippy::similar_names)]
 #![cfg_attr(coverage, feature(coverage_attribute))]
 
-use std::fmt;
+use std::{fmt, ops::Range};
 
-use serde::{Deserialize, Serialize};
-use value_type::value_type;
+// Only used by the serde tests
+#[cfg(all(test, not(feature = "serde")))]
+use serde_test as _;
 
-pub use self::chars::{Chars, Checkpoint, TokenHandle};
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+pub use self::fuzz::SourceAndSpan;
+#[cfg(feature = "tracing")]
+pub use self::tracing_fields::TracingFields;
+pub use self::{
+    chars::{
+        Balanced, BuildError, Chars, CharsBuilder, Checkpoint, ColumnPolicy,
+        EscapeError, InvalidUtf8, LineTerminators, LookaheadExceeded,
+        MalformedNumber, ResumeError, Snapshot, Stats, StringLiteral,
+        StringOptions, TokenHandle, Trace, WithTrivia,
+    },
+    display::{
+        DisplayBytes, Hyperlink, Localized, Locations, Visual, WithSource,
+        WithSourceName, Wording,
+    },
+    encoding::DecodeError,
+    ffi::SpanRaw,
+    multi_span::MultiSpan,
+    reporter::{ErrorReporter, Reported, Severity, Summary, TooManyErrors},
+    span_mapping::SpanMapping,
+    span_set::SpanSet,
+    span_tree::SpanTree,
+    spanned_error::{InSource, SpannedError},
+    spanned_str::SpannedStr,
+    token_diff::TokenDiff,
+};
 
+#[cfg(feature = "rkyv")]
+mod archive;
 mod chars;
+mod display;
+mod encoding;
+mod ffi;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+mod fuzz;
+#[cfg(feature = "html")]
+pub mod html;
+#[cfg(feature = "serde_json")]
+mod json;
+mod multi_span;
+#[cfg(feature = "unicode-normalization")]
+mod normalize;
+#[cfg(feature = "pyo3")]
+pub mod python;
+mod reporter;
+#[cfg(feature = "serde")]
+mod serialize;
+mod snippet;
+mod span_mapping;
+mod span_set;
+mod span_tree;
+mod spanned_error;
+mod spanned_str;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+pub mod testing;
+mod token_diff;
+#[cfg(feature = "toml_edit")]
+mod toml;
+#[cfg(feature = "tracing")]
+mod tracing_fields;
+#[cfg(any(feature = "serde_yaml", feature = "yaml-rust"))]
+mod yaml;
 
 /// Represents a region of a source file
 ///
@@ -75,50 +136,112 @@ mod chars;
 /// assert_eq!(format!("{}", Span::UNKNOWN), "???");
 /// assert_eq!(format!("{:#}", Span::UNKNOWN), "???");
 /// ```
-#[value_type(Copy)]
-#[cfg_attr(not(coverage), derive(Serialize, Deserialize))]
+///
+/// # Serialization
+/// With the `serde` feature enabled Span implements Serialize and
+/// Deserialize. Human readable formats (e.g. JSON) get a flat object:
+/// ```json
+/// {
+///   "start": 0,
+///   "end": 5,
+///   "start_byte": 0,
+///   "end_byte": 5,
+///   "start_line": 1,
+///   "start_col": 1,
+///   "end_line": 2,
+///   "end_col": 2
+/// }
+/// ```
+/// Other formats get the same eight fields as a tuple, in the same order.
+/// [Span::UNKNOWN] is represented as `None` (`null` in JSON).
+/// Deserializing an inconsistent span (see [InvalidSpan]) is an error
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug, Clone, Copy, PartialEq, Eq))
+)]
 pub struct Span {
     absolute: Option<AbsoluteSpan>,
     relative: RelativeSpan,
+    /// See [Span::provenance]
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    provenance: Provenance,
 }
 
-#[cfg_attr(coverage, coverage(off))]
-impl fmt::Display for Span {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.is_unknown() {
-            write!(f, "???")?;
-            return Ok(());
-        }
+/// What created a span, see [Span::provenance]. Empty unless the
+/// `provenance` feature is enabled, and never affects comparisons
+#[derive(Clone, Copy, Default)]
+struct Provenance {
+    #[cfg(feature = "provenance")]
+    label: Option<&'static str>,
+}
 
-        write!(
-            f,
-            "line {} column {}",
-            self.relative.start.line, self.relative.start.column
-        )?;
+impl Provenance {
+    const NONE: Provenance = Provenance {
+        #[cfg(feature = "provenance")]
+        label: None,
+    };
+}
 
-        // If the span is empty stop at printing the start character location
-        if self.relative.start == self.relative.end {
-            return Ok(());
-        }
+impl PartialEq for Provenance {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
 
-        // As above if the span is only 1 character wide
-        if self.relative.start.line == self.relative.end.line
-            && self.relative.start.column + 1 == self.relative.end.column
-        {
-            return Ok(());
-        }
+impl Eq for Provenance {}
+
+impl std::hash::Hash for Provenance {
+    fn hash<H: std::hash::Hasher>(&self, _: &mut H) {}
+}
 
-        // If # is specified and the span is more than 1 character wide print
-        // the end
-        if f.alternate() {
-            write!(f, " to")?;
-            #[allow(clippy::if_not_else)]
-            if self.relative.start.line != self.relative.end.line {
-                write!(f, " line {}", self.relative.end.line)?;
+/// Compact form to keep snapshots and `dbg!` output readable: the relative
+/// start and end as `line:column` followed by the absolute byte range
+///
+/// ```
+/// # use span::*;
+/// let mut chars = &mut Chars::new("123\n456");
+/// let start = chars.start_token();
+/// for _ in chars.take(5) {}
+/// let span = chars.end_token(start);
+/// assert_eq!(format!("{span:?}"), "Span(1:1..2:2 @ 0..5)");
+/// assert_eq!(format!("{:?}", Span::UNKNOWN), "Span(UNKNOWN)");
+/// ```
+///
+/// With the `provenance` feature enabled, the label attached by
+/// [Span::with_provenance] follows, e.g. `Span(1:1..1:4 @ 0..3 from ident)`
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Debug for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Span(")?;
+        match self.absolute {
+            None => write!(f, "UNKNOWN")?,
+            Some(absolute) => {
+                let RelativeSpan { start, end } = self.relative;
+                write!(
+                    f,
+                    "{}:{}..{}:{} @ {}..{}",
+                    start.line,
+                    start.column,
+                    end.line,
+                    end.column,
+                    absolute.start_byte,
+                    absolute.end_byte
+                )?;
             }
-            write!(f, " column {}", self.relative.end.column)?;
         }
-        Ok(())
+        if let Some(label) = self.provenance() {
+            write!(f, " from {label}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        display::write_span(f, *self, &Wording::ENGLISH)
     }
 }
 
@@ -127,6 +250,7 @@ impl Span {
     pub const UNKNOWN: Span = Span {
         absolute: None,
         relative: RelativeSpan::UNKNOWN,
+        provenance: Provenance::NONE,
     };
 
     /// Take a list of spans and produce a span that covers all of them
@@ -195,6 +319,82 @@ impl Span {
         result
     }
 
+    /// Join two spans that touch or overlap, unlike [Span::aggregate] which
+    /// also covers whatever is between them. Merging with [Span::UNKNOWN]
+    /// returns the other span
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "ab cd";
+    /// let a = testing::span_of(SRC, "a", 0);
+    /// let b = testing::span_of(SRC, "b", 0);
+    /// let d = testing::span_of(SRC, "d", 0);
+    /// let ab = Span::try_merge(b, a).unwrap();
+    /// assert_eq!(ab, testing::span_of(SRC, "ab", 0));
+    /// assert_eq!(Span::try_merge(ab, b), Ok(ab));
+    /// let gap = Span::try_merge(ab, d).unwrap_err().gap;
+    /// assert_eq!(gap, testing::span_of(SRC, " c", 0));
+    /// ```
+    /// # Errors
+    /// If there is a gap between the spans
+    pub fn try_merge(a: Span, b: Span) -> Result<Span, NotAdjacent> {
+        let (Some(x), Some(y)) = (a.absolute, b.absolute) else {
+            return Ok(Span::add(a, b));
+        };
+        let (first, first_relative, second, second_relative) =
+            if x.start <= y.start {
+                (x, a.relative, y, b.relative)
+            } else {
+                (y, b.relative, x, a.relative)
+            };
+        if first.end >= second.start {
+            return Ok(Span::add(a, b));
+        }
+        Err(NotAdjacent {
+            gap: Span {
+                absolute: Some(AbsoluteSpan {
+                    start: first.end,
+                    end: second.start,
+                    start_byte: first.end_byte,
+                    end_byte: second.start_byte,
+                }),
+                relative: RelativeSpan {
+                    start: first_relative.end,
+                    end: second_relative.start,
+                },
+                provenance: Provenance::NONE,
+            },
+        })
+    }
+
+    /// Check the invariants that hold for every span produced by [Chars]
+    pub(crate) fn validate(&self) -> Result<(), InvalidSpan> {
+        let Some(absolute) = self.absolute else {
+            if self.relative == RelativeSpan::UNKNOWN {
+                return Ok(());
+            }
+            return Err(InvalidSpan::MissingAbsolute);
+        };
+        let RelativeSpan { start, end } = self.relative;
+        if start == LineAndColumn::UNKNOWN || end == LineAndColumn::UNKNOWN {
+            return Err(InvalidSpan::MissingRelative);
+        }
+        if start.line == 0
+            || start.column == 0
+            || end.line == 0
+            || end.column == 0
+        {
+            return Err(InvalidSpan::ZeroLineOrColumn);
+        }
+        if absolute.end < absolute.start
+            || absolute.end_byte < absolute.start_byte
+            || end < start
+        {
+            return Err(InvalidSpan::EndBeforeStart);
+        }
+        Ok(())
+    }
+
     fn add(a: Span, b: Span) -> Span {
         if a.is_unknown() {
             return b;
@@ -205,16 +405,238 @@ impl Span {
         Span {
             absolute: AbsoluteSpan::add(a.absolute, b.absolute),
             relative: RelativeSpan::add(a.relative, b.relative),
+            provenance: Provenance::NONE,
         }
     }
 
-    /// Check if the span is Span::UNKNOWN, required as PartialEq is implemented
-    /// such that Span:UNKNOWN is equal to all spans
+    /// Check if the span is Span::UNKNOWN
     #[must_use]
     pub fn is_unknown(&self) -> bool {
         self.absolute.is_none()
     }
 
+    /// Lenient comparison intended for test assertions. Span::UNKNOWN matches
+    /// every span, otherwise this is the same as `==`.
+    ///
+    /// Equality on Span is structural so Span::UNKNOWN is only `==` to itself.
+    /// `matches` isn't transitive so it shouldn't be used to put spans in
+    /// collections.
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("123");
+    /// let start = chars.start_token();
+    /// let _ = chars.next();
+    /// let span = chars.end_token(start);
+    /// assert!(span.matches(&span));
+    /// assert!(span.matches(&Span::UNKNOWN));
+    /// assert!(Span::UNKNOWN.matches(&span));
+    /// assert_ne!(span, Span::UNKNOWN);
+    /// ```
+    #[must_use]
+    pub fn matches(&self, other: &Span) -> bool {
+        self.is_unknown() || other.is_unknown() || self == other
+    }
+
+    /// Label what created the span (e.g. the lexer or parser rule), for
+    /// debugging spans that point somewhere unexpected. The label is shown by
+    /// the span's Debug output and returned by [Span::provenance] but doesn't
+    /// affect comparisons. See also [Chars::end_token_from].
+    ///
+    /// Labels are only kept when the `provenance` feature is enabled,
+    /// otherwise this does nothing and costs nothing.
+    #[must_use]
+    #[cfg_attr(not(feature = "provenance"), expect(unused_variables))]
+    pub fn with_provenance(self, label: &'static str) -> Span {
+        Span {
+            #[cfg(feature = "provenance")]
+            provenance: Provenance { label: Some(label) },
+            ..self
+        }
+    }
+
+    /// The label attached by [Span::with_provenance], always None unless the
+    /// `provenance` feature is enabled
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = Chars::new("abc");
+    /// let start = chars.start_token();
+    /// let _ = chars.next();
+    /// let span = chars.end_token_from(start, "letter");
+    /// if cfg!(feature = "provenance") {
+    ///     assert_eq!(span.provenance(), Some("letter"));
+    ///     assert_eq!(
+    ///         format!("{span:?}"),
+    ///         "Span(1:1..1:2 @ 0..1 from letter)"
+    ///     );
+    /// } else {
+    ///     assert_eq!(span.provenance(), None);
+    /// }
+    /// assert_eq!(span, span.with_provenance("other"));
+    /// ```
+    #[must_use]
+    pub fn provenance(&self) -> Option<&'static str> {
+        #[cfg(feature = "provenance")]
+        return self.provenance.label;
+        #[cfg(not(feature = "provenance"))]
+        None
+    }
+
+    /// Attach the name of the source the span points into (usually a file
+    /// path) for display. Both the normal and alternate forms of [Span]'s
+    /// Display are supported, the name is printed as a prefix
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = &mut Chars::new("123\n456");
+    /// let start = chars.start_token();
+    /// for _ in chars.take(5) {}
+    /// let span = chars.end_token(start);
+    /// assert_eq!(
+    ///     format!("{}", span.with_source_name("path/to/file")),
+    ///     "path/to/file: line 1 column 1"
+    /// );
+    /// assert_eq!(
+    ///     format!("{:#}", span.with_source_name("path/to/file")),
+    ///     "path/to/file: line 1 column 1 to line 2 column 2"
+    /// );
+    /// assert_eq!(
+    ///     format!("{}", Span::UNKNOWN.with_source_name("path/to/file")),
+    ///     "path/to/file: ???"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_source_name<'a>(&self, name: &'a str) -> WithSourceName<'a> {
+        WithSourceName::new(*self, name)
+    }
+
+    /// Display the span like [Span::with_source_name], wrapped in an OSC 8
+    /// terminal hyperlink to `file://path#line` so clicking it opens the
+    /// file. `path` should be absolute for the link to work.
+    ///
+    /// Whether the terminal supports hyperlinks is guessed from the
+    /// environment (see [Hyperlink::detect]), if it doesn't or the span is
+    /// Span::UNKNOWN the plain text is printed. Use [Hyperlink::enabled] to
+    /// decide explicitly
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "let x = y;\nlet z = x;";
+    /// let span = testing::span_of(SRC, "z", 0);
+    /// let link = span.hyperlink("/src/my file.rs").enabled(true);
+    /// assert_eq!(
+    ///     format!("{link}"),
+    ///     "\x1b]8;;file:///src/my%20file.rs#2\x1b\\\
+    ///      /src/my file.rs: line 2 column 5\x1b]8;;\x1b\\"
+    /// );
+    /// let plain = span.hyperlink("/src/my file.rs").enabled(false);
+    /// assert_eq!(format!("{plain}"), "/src/my file.rs: line 2 column 5");
+    /// ```
+    #[must_use]
+    pub fn hyperlink<'a>(&self, path: &'a str) -> Hyperlink<'a> {
+        Hyperlink::new(*self, path)
+    }
+
+    /// Display the span followed by the source lines it covers with the span
+    /// underlined, for quick error messages that don't need anything more
+    /// elaborate. `source` must be the text the span points into. Both the
+    /// normal and alternate forms of [Span]'s Display are supported for the
+    /// first line
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "let x = y;\nlet z = x;";
+    /// let span = testing::span_of(SRC, "x;", 0);
+    /// assert_eq!(
+    ///     format!("{:#}", span.display_with(SRC)),
+    ///     "line 2 column 9 to column 11\n\
+    ///      2 | let z = x;\n  \
+    ///        |         ^^\n"
+    /// );
+    /// assert_eq!(format!("{}", Span::UNKNOWN.display_with(SRC)), "???\n");
+    /// ```
+    #[must_use]
+    pub fn display_with<'a>(&self, source: &'a str) -> WithSource<'a> {
+        WithSource::new(*self, source)
+    }
+
+    /// Display a list of locations where the same problem was found, showing
+    /// at most `max` of them and a count of the rest. The spans are sorted by
+    /// position, with Span::UNKNOWN last, and exact duplicates are removed so
+    /// the output is stable
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "a b a b a";
+    /// let spans = (0..3)
+    ///     .rev()
+    ///     .map(|n| testing::span_of(SRC, "a", n))
+    ///     .chain([testing::span_of(SRC, "a", 0)])
+    ///     .chain([testing::span_of(SRC, "b", 1)])
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     Span::summarize(&spans, 2).to_string(),
+    ///     "line 1 column 1, line 1 column 5 and 2 more locations"
+    /// );
+    /// assert_eq!(
+    ///     format!("{:#}", Span::summarize(&spans[..1], 2)),
+    ///     "line 1 column 9"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn summarize(spans: &[Span], max: usize) -> Locations {
+        Locations::new(spans, max)
+    }
+
+    /// Display the span as the range of bytes it covers rather than by line
+    /// and column. Intended for debugging lexers
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = &mut Chars::new("£23\n456");
+    /// let _ = chars.next();
+    /// let start = chars.start_token();
+    /// for _ in chars.take(4) {}
+    /// let span = chars.end_token(start);
+    /// assert_eq!(format!("{}", span.display_bytes()), "bytes 2..6");
+    /// assert_eq!(format!("{}", Span::UNKNOWN.display_bytes()), "???");
+    /// ```
+    #[must_use]
+    pub fn display_bytes(&self) -> DisplayBytes {
+        DisplayBytes::new(*self)
+    }
+
+    /// Display the span like its Display impl but with the words replaced,
+    /// e.g. for localization. Both the normal and alternate forms are
+    /// supported
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = &mut Chars::new("123\n456");
+    /// let start = chars.start_token();
+    /// for _ in chars.take(5) {}
+    /// let span = chars.end_token(start);
+    /// let wording = Wording {
+    ///     line: "Zeile",
+    ///     column: "Spalte",
+    ///     to: "bis",
+    ///     unknown: "unbekannt",
+    /// };
+    /// assert_eq!(
+    ///     format!("{:#}", span.localized(wording)),
+    ///     "Zeile 1 Spalte 1 bis Zeile 2 Spalte 2"
+    /// );
+    /// assert_eq!(
+    ///     format!("{}", Span::UNKNOWN.localized(wording)),
+    ///     "unbekannt"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn localized<'a>(&self, wording: Wording<'a>) -> Localized<'a> {
+        Localized::new(*self, wording)
+    }
+
     /// Start Line (1 indexed)
     ///
     /// ```
@@ -292,7 +714,10 @@ impl Span {
         self.absolute.map(|_| self.relative.end.line)
     }
 
-    /// Position on the end line of the end of the token (1 indexed)
+    /// Position on the end line of the end of the token (1 indexed). The end
+    /// is exclusive, this is the column one past the last character in the
+    /// token. See [Span::end_position_inclusive] for the position of the last
+    /// character
     ///
     /// ```
     /// # use span::*;
@@ -317,7 +742,291 @@ impl Span {
         self.absolute.map(|_| self.relative.end.column)
     }
 
-    /// Start of the token relative to the start of the text
+    /// Column the span starts at with tabs expanded to `tab_width` and other
+    /// characters taking their display width, i.e. where the user sees it in
+    /// a terminal or editor. `source` must be the text the span points into.
+    /// Returns None for Span::UNKNOWN and spans that don't fit `source`.
+    ///
+    /// Spans produced by a [Chars] using [ColumnPolicy::Visual] already have
+    /// visual columns, this is for working them out after the fact
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "\tif x {\n\t\t\u{4f60}y\n";
+    /// let y = testing::span_of(SRC, "y", 0);
+    /// assert_eq!(y.start_position_on_start_line(), Some(4));
+    /// assert_eq!(y.visual_column(SRC, 4), Some(11));
+    /// assert_eq!(y.visual_end_column(SRC, 4), Some(12));
+    /// assert_eq!(Span::UNKNOWN.visual_column(SRC, 4), None);
+    /// ```
+    #[must_use]
+    pub fn visual_column(
+        &self,
+        source: &str,
+        tab_width: usize,
+    ) -> Option<usize> {
+        let absolute = self.absolute.filter(|_| self.is_valid_for(source))?;
+        Some(Span::visual_column_of(
+            source,
+            absolute.start_byte,
+            tab_width,
+        ))
+    }
+
+    /// Column one past the end of the span with tabs expanded, see
+    /// [Span::visual_column]
+    #[must_use]
+    pub fn visual_end_column(
+        &self,
+        source: &str,
+        tab_width: usize,
+    ) -> Option<usize> {
+        let absolute = self.absolute.filter(|_| self.is_valid_for(source))?;
+        Some(Span::visual_column_of(source, absolute.end_byte, tab_width))
+    }
+
+    /// Display the span with visual columns, see [Span::visual_column]. Both
+    /// the normal and alternate forms of [Span]'s Display are supported
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "\t\tlet x = y;";
+    /// let span = testing::span_of(SRC, "x = y", 0);
+    /// assert_eq!(format!("{span:#}"), "line 1 column 7 to column 12");
+    /// assert_eq!(
+    ///     format!("{:#}", span.display_visual(SRC, 8)),
+    ///     "line 1 column 21 to column 26"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn display_visual(&self, source: &str, tab_width: usize) -> Visual {
+        let span = match (
+            self.visual_column(source, tab_width),
+            self.visual_end_column(source, tab_width),
+        ) {
+            (Some(start), Some(end)) => {
+                let mut span = *self;
+                span.relative.start.column = start;
+                span.relative.end.column = end;
+                span
+            }
+            _ => *self,
+        };
+        Visual::new(span)
+    }
+
+    /// Line and column of the start of the token, see [Span::start_line] and
+    /// [Span::start_position_on_start_line]
+    ///
+    /// ```
+    /// # use span::*;
+    /// let span = testing::span_of("123\n456", "3\n45", 0);
+    /// assert_eq!(
+    ///     span.start_line_and_column(),
+    ///     Some(LineAndColumn { line: 1, column: 3 })
+    /// );
+    /// assert_eq!(
+    ///     span.end_line_and_column(),
+    ///     Some(LineAndColumn { line: 2, column: 3 })
+    /// );
+    /// assert_eq!(Span::UNKNOWN.start_line_and_column(), None);
+    /// ```
+    #[must_use]
+    pub fn start_line_and_column(&self) -> Option<LineAndColumn> {
+        self.absolute.map(|_| self.relative.start)
+    }
+
+    /// Line and column of the (exclusive) end of the token, see
+    /// [Span::end_line] and [Span::end_position_on_end_line]
+    #[must_use]
+    pub fn end_line_and_column(&self) -> Option<LineAndColumn> {
+        self.absolute.map(|_| self.relative.end)
+    }
+
+    /// Span of `source` from `start` up to (but not including) `end`, columns
+    /// count characters as with [ColumnPolicy::Chars]. Returns None if either
+    /// position is outside the text or `end` is before `start`
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "let x = 1;\nlet y = 2;";
+    /// let span = Span::from_positions(
+    ///     SRC,
+    ///     LineAndColumn { line: 2, column: 5 },
+    ///     LineAndColumn { line: 2, column: 6 },
+    /// );
+    /// assert_eq!(span, Some(testing::span_of(SRC, "y", 0)));
+    /// let past_end = LineAndColumn { line: 1, column: 12 };
+    /// assert_eq!(Span::from_positions(SRC, past_end, past_end), None);
+    /// ```
+    #[must_use]
+    pub fn from_positions(
+        source: &str,
+        start: LineAndColumn,
+        end: LineAndColumn,
+    ) -> Option<Span> {
+        let start = start.byte_offset_in(source)?;
+        let end = end.byte_offset_in(source)?;
+        (start <= end).then(|| Span::from_byte_range(source, start, end))
+    }
+
+    /// Span of the character at `line` and `column` (1 indexed, columns
+    /// count characters as with [ColumnPolicy::Chars]), e.g. to turn a
+    /// parser's error position into a span for rendering. At the end of a
+    /// line or of the input the span is empty. Returns None if the position
+    /// is outside the text
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "a = 1\nb = ?";
+    /// let span = Span::from_line_col(SRC, 2, 5).unwrap();
+    /// assert_eq!(span, testing::span_of(SRC, "?", 0));
+    /// let end = Span::from_line_col(SRC, 1, 6).unwrap();
+    /// assert_eq!(format!("{end:#}"), "line 1 column 6");
+    /// assert_eq!(Span::from_line_col(SRC, 3, 1), None);
+    /// ```
+    #[must_use]
+    pub fn from_line_col(
+        source: &str,
+        line: usize,
+        column: usize,
+    ) -> Option<Span> {
+        let start = LineAndColumn { line, column }.byte_offset_in(source)?;
+        Some(Span::char_at(source, start))
+    }
+
+    /// Span of the byte range `range` of `source`, e.g. from a parser that
+    /// reports byte ranges like `toml_edit`. Returns None if the range is
+    /// reversed, extends past the end of `source` or doesn't fall on
+    /// character boundaries
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "é = 1";
+    /// assert_eq!(
+    ///     Span::from_range(SRC, 5..6),
+    ///     Some(testing::span_of(SRC, "1", 0))
+    /// );
+    /// assert_eq!(Span::from_range(SRC, 1..2), None);
+    /// assert_eq!(Span::from_range(SRC, 5..7), None);
+    /// ```
+    #[must_use]
+    pub fn from_range(source: &str, range: Range<usize>) -> Option<Span> {
+        let _ = source.get(range.clone())?;
+        Some(Span::from_byte_range(source, range.start, range.end))
+    }
+
+    /// Visual column of the byte offset `byte` in `source`, see
+    /// [Span::visual_column]
+    fn visual_column_of(source: &str, byte: usize, tab_width: usize) -> usize {
+        let line_start = source[..byte].rfind('\n').map_or(0, |nl| nl + 1);
+        let policy = ColumnPolicy::Visual { tab_width };
+        source[line_start..byte]
+            .chars()
+            .fold(1, |column, c| column + policy.width(c, column))
+    }
+
+    /// Span of the character starting at byte offset `start` of `source`,
+    /// empty if it is a newline or the end of the input
+    pub(crate) fn char_at(source: &str, start: usize) -> Span {
+        let end = match source[start..].chars().next() {
+            Some('\n') | None => start,
+            Some(c) => start + c.len_utf8(),
+        };
+        Span::from_byte_range(source, start, end)
+    }
+
+    /// Line and column (1 indexed) of the last character in the token, for
+    /// consumers that expect inclusive ranges (e.g. editor selections).
+    /// Returns None for empty spans and Span::UNKNOWN. Columns count
+    /// characters as with [ColumnPolicy::Chars], for spans made with another
+    /// policy the result may not be the start of the last character.
+    ///
+    /// If the last character is a newline its column can only be recovered
+    /// from the span if the token also starts on that line, otherwise this
+    /// returns None. [Span::end_position_inclusive_in] handles that case
+    /// using the source text.
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = &mut Chars::new("123\n456\n");
+    /// let span1 = {
+    ///     let start = chars.start_token();
+    ///     for _ in chars.take(3) {}
+    ///     chars.end_token(start)
+    /// };
+    /// let span2 = {
+    ///     let start = chars.start_token();
+    ///     for _ in chars.take(5) {}
+    ///     chars.end_token(start)
+    /// };
+    /// let empty = {
+    ///     let start = chars.start_token();
+    ///     chars.end_token(start)
+    /// };
+    /// assert_eq!(span1.end_position_on_end_line(), Some(4));
+    /// assert_eq!(span1.end_position_inclusive(), Some((1, 3)));
+    /// // Ends with the newline at line 2 column 4
+    /// assert_eq!(span2.end_line(), Some(3));
+    /// assert_eq!(span2.end_position_on_end_line(), Some(1));
+    /// assert_eq!(span2.end_position_inclusive(), None);
+    /// assert_eq!(empty.end_position_inclusive(), None);
+    /// assert_eq!(Span::UNKNOWN.end_position_inclusive(), None);
+    /// ```
+    #[must_use]
+    pub fn end_position_inclusive(&self) -> Option<(usize, usize)> {
+        let absolute = self.absolute?;
+        if absolute.start == absolute.end {
+            return None;
+        }
+        let RelativeSpan { start, end } = self.relative;
+        if end.column > 1 {
+            return Some((end.line, end.column - 1));
+        }
+        // The last character is a newline
+        if start.line + 1 == end.line {
+            let column = start.column + (absolute.end - absolute.start) - 1;
+            return Some((start.line, column));
+        }
+        None
+    }
+
+    /// As [Span::end_position_inclusive] but uses the source text the span
+    /// points into to find the position of a trailing newline. Columns count
+    /// characters as with [ColumnPolicy::Chars]. Returns None if the span
+    /// doesn't fit in `source`
+    ///
+    /// ```
+    /// # use span::*;
+    /// let source = "123\n456\n";
+    /// let mut chars = &mut Chars::new(source);
+    /// let _ = chars.next();
+    /// let start = chars.start_token();
+    /// for _ in chars.take(7) {}
+    /// let span = chars.end_token(start);
+    /// assert_eq!(span.end_position_inclusive(), None);
+    /// assert_eq!(span.end_position_inclusive_in(source), Some((2, 4)));
+    /// ```
+    #[must_use]
+    pub fn end_position_inclusive_in(
+        &self,
+        source: &str,
+    ) -> Option<(usize, usize)> {
+        if let Some(position) = self.end_position_inclusive() {
+            return Some(position);
+        }
+        let absolute = self.absolute?;
+        if absolute.start == absolute.end {
+            return None;
+        }
+        let before = source.get(..absolute.end_byte.checked_sub(1)?)?;
+        let line_start = before.rfind('\n').map_or(0, |nl| nl + 1);
+        let column = before[line_start..].chars().count() + 1;
+        Some((self.relative.end.line - 1, column))
+    }
+
+    /// Start of the token relative to the start of the text, counted in
+    /// characters. See [Span::start_byte] for the byte offset
     ///
     /// ```
     /// # use span::*;
@@ -342,7 +1051,10 @@ impl Span {
         Some(self.absolute?.start)
     }
 
-    /// Length of the token (may span multiple lines)
+    /// Length of the token in characters (may span multiple lines)
+    ///
+    /// Deprecated as it is easily mistaken for the length in bytes, use
+    /// [Span::len_chars] or [Span::len_bytes]
     ///
     /// ```
     /// # use span::*;
@@ -364,26 +1076,544 @@ impl Span {
     /// ```
     #[must_use]
     #[expect(clippy::len_without_is_empty)]
+    #[deprecated(note = "ambiguous, use len_chars or len_bytes instead")]
     pub fn len(&self) -> Option<usize> {
+        self.len_chars()
+    }
+
+    /// Start of the token relative to the start of the text, counted in bytes.
+    /// Suitable for slicing the source text
+    ///
+    /// ```
+    /// # use span::*;
+    /// let source = "£1 £2";
+    /// let mut chars = &mut Chars::new(source);
+    /// for _ in chars.take(3) {}
+    /// let span = {
+    ///     let start = chars.start_token();
+    ///     for _ in chars.take(2) {}
+    ///     chars.end_token(start)
+    /// };
+    /// assert_eq!(span.start(), Some(3));
+    /// assert_eq!(span.start_byte(), Some(4));
+    /// assert_eq!(Span::UNKNOWN.start_byte(), None);
+    /// ```
+    #[must_use]
+    pub fn start_byte(&self) -> Option<usize> {
+        Some(self.absolute?.start_byte)
+    }
+
+    /// Length of the token in characters (may span multiple lines)
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = &mut Chars::new("£1\n£2");
+    /// let start = chars.start_token();
+    /// for _ in chars.take(5) {}
+    /// let span = chars.end_token(start);
+    /// assert_eq!(span.len_chars(), Some(5));
+    /// assert_eq!(Span::UNKNOWN.len_chars(), None);
+    /// ```
+    #[must_use]
+    pub fn len_chars(&self) -> Option<usize> {
         self.absolute.map(|s| s.end - s.start)
     }
+
+    /// Length of the token in bytes (may span multiple lines)
+    ///
+    /// ```
+    /// # use span::*;
+    /// let source = "£1\n£2";
+    /// let mut chars = &mut Chars::new(source);
+    /// let start = chars.start_token();
+    /// for _ in chars.take(5) {}
+    /// let span = chars.end_token(start);
+    /// assert_eq!(span.len_bytes(), Some(7));
+    ///
+    /// let start = span.start_byte().unwrap();
+    /// let end = start + span.len_bytes().unwrap();
+    /// assert_eq!(&source[start..end], source);
+    /// assert_eq!(Span::UNKNOWN.len_bytes(), None);
+    /// ```
+    #[must_use]
+    pub fn len_bytes(&self) -> Option<usize> {
+        self.absolute.map(|s| s.end_byte - s.start_byte)
+    }
+
+    /// Number of columns covered by a span that starts and ends on the same
+    /// line. Returns None for multi-line spans and Span::UNKNOWN
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = &mut Chars::new("123\n456");
+    /// let span1 = {
+    ///     let start = chars.start_token();
+    ///     for _ in chars.take(3) {}
+    ///     chars.end_token(start)
+    /// };
+    /// let span2 = {
+    ///     let start = chars.start_token();
+    ///     for _ in chars.take(3) {}
+    ///     chars.end_token(start)
+    /// };
+    /// assert_eq!(span1.width(), Some(3));
+    /// assert_eq!(span2.width(), None);
+    /// assert_eq!(Span::UNKNOWN.width(), None);
+    /// ```
+    #[must_use]
+    pub fn width(&self) -> Option<usize> {
+        let RelativeSpan { start, end } = self.relative;
+        self.absolute
+            .filter(|_| start.line == end.line)
+            .map(|_| end.column - start.column)
+    }
+
+    /// Split a span into one span per source line it touches, `source` must
+    /// be the text the span points into. Newlines aren't included in any of
+    /// the per-line spans, so a line that only contributes its newline
+    /// produces an empty span. Span::UNKNOWN and spans that don't fit in
+    /// `source` produce nothing
+    ///
+    /// ```
+    /// # use span::*;
+    /// let source = "123\n456\n789";
+    /// let mut chars = &mut Chars::new(source);
+    /// let _ = chars.next();
+    /// let start = chars.start_token();
+    /// for _ in chars.take(8) {}
+    /// let span = chars.end_token(start);
+    /// assert_eq!(
+    ///     span.lines(source)
+    ///         .map(|span| format!("{span:#}"))
+    ///         .collect::<Vec<_>>(),
+    ///     [
+    ///         "line 1 column 2 to column 4",
+    ///         "line 2 column 1 to column 4",
+    ///         "line 3 column 1"
+    ///     ]
+    /// );
+    /// assert_eq!(Span::UNKNOWN.lines(source).count(), 0);
+    /// ```
+    pub fn lines<'a>(
+        &self,
+        source: &'a str,
+    ) -> impl Iterator<Item = Span> + 'a {
+        let start = self.absolute.and_then(|absolute| {
+            let text = source.get(absolute.start_byte..absolute.end_byte)?;
+            Some((text, absolute.start, absolute.start_byte))
+        });
+        let relative = self.relative.start;
+        start.into_iter().flat_map(move |(text, loc, byte)| {
+            text.split('\n').scan(
+                (loc, byte, relative),
+                |(loc, byte, start), segment| {
+                    let chars = segment.chars().count();
+                    let span = Span {
+                        absolute: Some(AbsoluteSpan {
+                            start: *loc,
+                            end: *loc + chars,
+                            start_byte: *byte,
+                            end_byte: *byte + segment.len(),
+                        }),
+                        relative: RelativeSpan {
+                            start: *start,
+                            end: LineAndColumn {
+                                line: start.line,
+                                column: start.column + chars,
+                            },
+                        },
+                        provenance: Provenance::NONE,
+                    };
+                    *loc += chars + 1;
+                    *byte += segment.len() + 1;
+                    *start = LineAndColumn {
+                        line: start.line + 1,
+                        column: 1,
+                    };
+                    Some(span)
+                },
+            )
+        })
+    }
+
+    /// One character span covering the first character of this span, e.g.
+    /// to point at the opening delimiter of a larger construct. `source` must
+    /// be the text the span points into. Returns None for empty spans,
+    /// Span::UNKNOWN and spans that don't fit in `source`
+    ///
+    /// ```
+    /// # use span::*;
+    /// let source = "«a\nb»";
+    /// let mut chars = &mut Chars::new(source);
+    /// let start = chars.start_token();
+    /// for _ in chars.take(5) {}
+    /// let span = chars.end_token(start);
+    /// let first = span.first_char(source).unwrap();
+    /// assert_eq!(format!("{first:#}"), "line 1 column 1");
+    /// assert_eq!(first.len_bytes(), Some(2));
+    /// assert_eq!(Span::UNKNOWN.first_char(source), None);
+    /// ```
+    #[must_use]
+    pub fn first_char(&self, source: &str) -> Option<Span> {
+        let absolute = self.absolute?;
+        let c = source
+            .get(absolute.start_byte..absolute.end_byte)?
+            .chars()
+            .next()?;
+        let start = self.relative.start;
+        let end = if c == '\n' {
+            LineAndColumn {
+                line: start.line + 1,
+                column: 1,
+            }
+        } else {
+            LineAndColumn {
+                line: start.line,
+                column: start.column + 1,
+            }
+        };
+        Some(Span {
+            absolute: Some(AbsoluteSpan {
+                start: absolute.start,
+                end: absolute.start + 1,
+                start_byte: absolute.start_byte,
+                end_byte: absolute.start_byte + c.len_utf8(),
+            }),
+            relative: RelativeSpan { start, end },
+            provenance: Provenance::NONE,
+        })
+    }
+
+    /// One character span covering the last character of this span, e.g. to
+    /// point at the closing delimiter of a larger construct. `source` must be
+    /// the text the span points into. Returns None for empty spans,
+    /// Span::UNKNOWN and spans that don't fit in `source`
+    ///
+    /// ```
+    /// # use span::*;
+    /// let source = "«a\nb»";
+    /// let mut chars = &mut Chars::new(source);
+    /// let start = chars.start_token();
+    /// for _ in chars.take(5) {}
+    /// let span = chars.end_token(start);
+    /// let last = span.last_char(source).unwrap();
+    /// assert_eq!(format!("{last:#}"), "line 2 column 2");
+    /// assert_eq!(last.start_byte(), Some(5));
+    /// assert_eq!(last.len_bytes(), Some(2));
+    /// assert_eq!(Span::UNKNOWN.last_char(source), None);
+    /// ```
+    #[must_use]
+    pub fn last_char(&self, source: &str) -> Option<Span> {
+        let absolute = self.absolute?;
+        let c = source
+            .get(absolute.start_byte..absolute.end_byte)?
+            .chars()
+            .next_back()?;
+        let (line, column) = self.end_position_inclusive_in(source)?;
+        Some(Span {
+            absolute: Some(AbsoluteSpan {
+                start: absolute.end - 1,
+                end: absolute.end,
+                start_byte: absolute.end_byte - c.len_utf8(),
+                end_byte: absolute.end_byte,
+            }),
+            relative: RelativeSpan {
+                start: LineAndColumn { line, column },
+                end: self.relative.end,
+            },
+            provenance: Provenance::NONE,
+        })
+    }
+
+    /// Check that the span can be used to slice `source`, i.e. that it fits
+    /// in the text and starts and ends on character boundaries. Span::UNKNOWN
+    /// is never valid
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = &mut Chars::new("123456");
+    /// let start = chars.start_token();
+    /// for _ in chars.take(5) {}
+    /// let span = chars.end_token(start);
+    /// assert!(span.is_valid_for("123456"));
+    /// assert!(!span.is_valid_for("123"));
+    /// assert!(!Span::UNKNOWN.is_valid_for("123456"));
+    /// ```
+    #[must_use]
+    pub fn is_valid_for(&self, source: &str) -> bool {
+        self.absolute.is_some_and(|absolute| {
+            source.get(absolute.start_byte..absolute.end_byte).is_some()
+        })
+    }
+
+    /// Defend against spans that don't belong to `source` (e.g. from a stale
+    /// cache) by truncating them to the end of the text. If the start or end
+    /// doesn't fall on a character boundary it is moved forward to the next
+    /// one. Spans that are already valid for `source` and Span::UNKNOWN are
+    /// returned unchanged
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = &mut Chars::new("123\n456");
+    /// let start = chars.start_token();
+    /// for _ in chars.take(6) {}
+    /// let span = chars.end_token(start);
+    ///
+    /// let clamped = span.clamp("123\n4");
+    /// assert!(clamped.is_valid_for("123\n4"));
+    /// assert_eq!(format!("{clamped:#}"), "line 1 column 1 to line 2 column 2");
+    /// assert_eq!(span.clamp("123\n456"), span);
+    /// ```
+    #[must_use]
+    pub fn clamp(&self, source: &str) -> Span {
+        let Some(absolute) = self.absolute else {
+            return *self;
+        };
+        if self.is_valid_for(source) {
+            return *self;
+        }
+        Span::from_byte_range(source, absolute.start_byte, absolute.end_byte)
+    }
+
+    /// Span covering the given range of byte offsets into `source`, computed
+    /// by running [Chars] over the text. Offsets that aren't on a character
+    /// boundary are moved forward to the next one and offsets past the end
+    /// of the text are truncated.
+    pub(crate) fn from_byte_range(
+        source: &str,
+        start: usize,
+        end: usize,
+    ) -> Span {
+        fn advance(chars: &mut Chars, byte: &mut usize, target: usize) {
+            while *byte < target
+                && let Some(c) = chars.next()
+            {
+                *byte += c.len_utf8();
+            }
+        }
+
+        let mut chars = Chars::new(source);
+        let mut byte = 0;
+        advance(&mut chars, &mut byte, start);
+        let start = chars.start_token();
+        advance(&mut chars, &mut byte, end);
+        chars.end_token(start)
+    }
+
+    /// Number of bytes between the nearer ends of two spans, 0 if they are
+    /// adjacent. Returns None if the spans overlap or either is Span::UNKNOWN
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = &mut Chars::new("ab  cd");
+    /// let span1 = {
+    ///     let start = chars.start_token();
+    ///     for _ in chars.take(2) {}
+    ///     chars.end_token(start)
+    /// };
+    /// let span2 = {
+    ///     let start = chars.start_token();
+    ///     for _ in chars.take(2) {}
+    ///     chars.end_token(start)
+    /// };
+    /// let span3 = {
+    ///     let start = chars.start_token();
+    ///     for _ in chars.take(2) {}
+    ///     chars.end_token(start)
+    /// };
+    /// assert_eq!(span1.gap_to(&span2), Some(0));
+    /// assert_eq!(span1.gap_to(&span3), Some(2));
+    /// assert_eq!(span3.gap_to(&span1), Some(2));
+    /// let both = Span::aggregate(&[span1, span2]);
+    /// assert_eq!(both.gap_to(&span2), None);
+    /// assert_eq!(span1.gap_to(&Span::UNKNOWN), None);
+    /// ```
+    #[must_use]
+    pub fn gap_to(&self, other: &Span) -> Option<usize> {
+        let a = self.absolute?;
+        let b = other.absolute?;
+        if a.end_byte <= b.start_byte {
+            Some(b.start_byte - a.end_byte)
+        } else if b.end_byte <= a.start_byte {
+            Some(a.start_byte - b.end_byte)
+        } else {
+            None
+        }
+    }
+
+    /// Check whether the span touches the given line (1 indexed). A span that
+    /// ends with a newline doesn't cover the following line. Returns None for
+    /// Span::UNKNOWN
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = &mut Chars::new("123\n456\n789");
+    /// let _ = chars.next();
+    /// let start = chars.start_token();
+    /// for _ in chars.take(7) {}
+    /// let span = chars.end_token(start);
+    /// assert_eq!(format!("{span:#}"), "line 1 column 2 to line 3 column 1");
+    /// assert_eq!(span.covers_line(1), Some(true));
+    /// assert_eq!(span.covers_line(2), Some(true));
+    /// assert_eq!(span.covers_line(3), Some(false));
+    /// assert_eq!(Span::UNKNOWN.covers_line(1), None);
+    /// ```
+    #[must_use]
+    pub fn covers_line(&self, line: usize) -> Option<bool> {
+        let RelativeSpan { start, end } = self.relative;
+        let last = if end.column == 1 && end.line > start.line {
+            end.line - 1
+        } else {
+            end.line
+        };
+        self.absolute.map(|_| (start.line..=last).contains(&line))
+    }
+
+    /// Check whether the character at `line` and `column` (both 1 indexed) is
+    /// inside the span, e.g. to find the token under an editor's cursor. The
+    /// end is exclusive so empty spans contain nothing. Returns None for
+    /// Span::UNKNOWN
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "f(a,\n  b)";
+    /// let args = testing::span_of(SRC, "a,\n  b", 0);
+    /// assert_eq!(format!("{args:#}"), "line 1 column 3 to line 2 column 4");
+    /// assert_eq!(args.contains_position(1, 2), Some(false));
+    /// assert_eq!(args.contains_position(1, 3), Some(true));
+    /// assert_eq!(args.contains_position(1, 5), Some(true));
+    /// assert_eq!(args.contains_position(2, 1), Some(true));
+    /// assert_eq!(args.contains_position(2, 4), Some(false));
+    /// assert_eq!(Span::UNKNOWN.contains_position(1, 1), None);
+    /// ```
+    #[must_use]
+    pub fn contains_position(
+        &self,
+        line: usize,
+        column: usize,
+    ) -> Option<bool> {
+        let RelativeSpan { start, end } = self.relative;
+        let position = (line, column);
+        self.absolute.map(|_| {
+            (start.line, start.column) <= position
+                && position < (end.line, end.column)
+        })
+    }
+
+    /// Sort a collection of spans and merge any that overlap or touch into
+    /// maximal runs. As with [Span::aggregate] unknown spans don't contribute
+    /// to the result, they are removed
+    ///
+    /// ```
+    /// # use span::*;
+    /// let mut chars = &mut Chars::new("ab cd");
+    /// let mut spans = Vec::new();
+    /// for _ in 0..5 {
+    ///     let start = chars.start_token();
+    ///     let _ = chars.next();
+    ///     spans.push(chars.end_token(start));
+    /// }
+    /// let space = spans.remove(2);
+    /// spans.reverse();
+    /// spans.push(Span::UNKNOWN);
+    /// Span::coalesce(&mut spans);
+    /// assert_eq!(
+    ///     spans.iter().map(|s| format!("{s:#}")).collect::<Vec<_>>(),
+    ///     ["line 1 column 1 to column 3", "line 1 column 4 to column 6"]
+    /// );
+    ///
+    /// spans.push(space);
+    /// Span::coalesce(&mut spans);
+    /// assert_eq!(
+    ///     spans.iter().map(|s| format!("{s:#}")).collect::<Vec<_>>(),
+    ///     ["line 1 column 1 to column 6"]
+    /// );
+    /// ```
+    pub fn coalesce(spans: &mut Vec<Span>) {
+        spans.retain(|span| !span.is_unknown());
+        spans.sort_by_key(|span| {
+            span.absolute.map(|a| (a.start_byte, a.end_byte))
+        });
+        spans.dedup_by(|next, current| {
+            if next.start_byte() <= current.absolute.map(|a| a.end_byte) {
+                *current = Span::add(*current, *next);
+                true
+            } else {
+                false
+            }
+        });
+    }
+}
+
+/// Error returned by [Span::try_merge] for spans that don't touch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NotAdjacent {
+    /// The text between the two spans
+    pub gap: Span,
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for NotAdjacent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:#}: gap between spans that should be adjacent",
+            self.gap
+        )
+    }
+}
+
+impl std::error::Error for NotAdjacent {}
+
+/// Reasons a span can be rejected as inconsistent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidSpan {
+    /// The end of the span is before the start
+    EndBeforeStart,
+    /// Lines and columns are 1 indexed so can't be 0
+    ZeroLineOrColumn,
+    /// The line and column are known but the absolute position is not
+    MissingAbsolute,
+    /// The absolute position is known but the line and column are not
+    MissingRelative,
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for InvalidSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidSpan::EndBeforeStart => {
+                write!(f, "span ends before it starts")
+            }
+            InvalidSpan::ZeroLineOrColumn => {
+                write!(f, "span lines and columns are 1 indexed, found 0")
+            }
+            InvalidSpan::MissingAbsolute => {
+                write!(f, "span has a line and column but no absolute position")
+            }
+            InvalidSpan::MissingRelative => {
+                write!(
+                    f,
+                    "span has an absolute position but no line and column"
+                )
+            }
+        }
+    }
 }
 
-// #[cfg_attr(coverage, coverage(off))]
-// impl PartialEq for Span {
-//     fn eq(&self, other: &Span) -> bool {
-//         if self.is_unknown() || other.is_unknown() {
-//             return true;
-//         }
-//         self.absolute == other.absolute && self.relative == other.relative
-//     }
-// }
-
-#[value_type(Copy)]
-#[cfg_attr(not(coverage), derive(Serialize, Deserialize))]
+impl std::error::Error for InvalidSpan {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug, Clone, Copy, PartialEq, Eq))
+)]
 struct AbsoluteSpan {
     start: usize,
     end: usize,
+    start_byte: usize,
+    end_byte: usize,
 }
 
 impl AbsoluteSpan {
@@ -396,12 +1626,18 @@ impl AbsoluteSpan {
         Some(AbsoluteSpan {
             start: usize::min(a.start, b.start),
             end: usize::max(a.end, b.end),
+            start_byte: usize::min(a.start_byte, b.start_byte),
+            end_byte: usize::max(a.end_byte, b.end_byte),
         })
     }
 }
 
-#[value_type(Copy)]
-#[cfg_attr(not(coverage), derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug, Clone, Copy, PartialEq, Eq))
+)]
 struct RelativeSpan {
     start: LineAndColumn,
     end: LineAndColumn,
@@ -415,17 +1651,25 @@ impl RelativeSpan {
 
     fn add(a: RelativeSpan, b: RelativeSpan) -> RelativeSpan {
         RelativeSpan {
-            start: LineAndColumn::min(a.start, b.start),
-            end: LineAndColumn::max(a.end, b.end),
+            start: Ord::min(a.start, b.start),
+            end: Ord::max(a.end, b.end),
         }
     }
 }
 
-#[value_type(Copy)]
-#[cfg_attr(not(coverage), derive(Serialize, Deserialize))]
-struct LineAndColumn {
-    line: usize,
-    column: usize,
+/// Position in the source as a line and column, both 1 indexed. Ordered by
+/// line then column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug, Clone, Copy, PartialEq, Eq))
+)]
+pub struct LineAndColumn {
+    /// Line (1 indexed)
+    pub line: usize,
+    /// Column (1 indexed)
+    pub column: usize,
 }
 
 impl LineAndColumn {
@@ -434,14 +1678,22 @@ impl LineAndColumn {
         column: usize::MAX,
     };
 
-    fn min(a: LineAndColumn, b: LineAndColumn) -> LineAndColumn {
-        let (line, column) = (a.line, a.column).min((b.line, b.column));
-        LineAndColumn { line, column }
-    }
-
-    fn max(a: LineAndColumn, b: LineAndColumn) -> LineAndColumn {
-        let (line, column) = (a.line, a.column).max((b.line, b.column));
-        LineAndColumn { line, column }
+    /// Byte offset of the position in `source`, counting columns in
+    /// characters. The column after the last character on a line is allowed,
+    /// positions outside the text are None
+    fn byte_offset_in(self, source: &str) -> Option<usize> {
+        let line_start = match self.line.checked_sub(2) {
+            None if self.line == 1 => 0,
+            None => return None,
+            Some(n) => source.match_indices('\n').nth(n)?.0 + 1,
+        };
+        let line = source[line_start..].split('\n').next().unwrap_or_default();
+        let column = line
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([line.len()])
+            .nth(self.column.checked_sub(1)?)?;
+        Some(line_start + column)
     }
 }
 
@@ -457,6 +1709,15 @@ mod test {
 
         use super::*;
 
+        #[test]
+        fn end_position_inclusive_in_multibyte_line() {
+            let source = "x\n£😀\n";
+            let span = Span::from_byte_range(source, 0, source.len());
+            assert_eq!(span.end_position_inclusive(), None);
+            assert_eq!(span.end_position_inclusive_in(source), Some((2, 3)));
+            assert_eq!(span.end_position_inclusive_in("x\n"), None);
+        }
+
         #[cfg(debug_assertions)]
         #[test]
         #[should_panic(
@@ -472,18 +1733,33 @@ mod test {
             assert_eq!(Span::aggregate(&[]), Span::UNKNOWN);
         }
 
+        #[test]
+        fn try_merge_unknown_and_line_gap() {
+            const SRC: &str = "a\nb";
+            let a = testing::span_of(SRC, "a", 0);
+            let b = testing::span_of(SRC, "b", 0);
+            assert_eq!(Span::try_merge(Span::UNKNOWN, b), Ok(b));
+            let gap = Span::try_merge(b, a).unwrap_err().gap;
+            assert_eq!(gap, testing::span_of(SRC, "\n", 0));
+            assert_eq!(
+                format!("{gap:#}"),
+                "line 1 column 2 to line 2 column 1"
+            );
+        }
+
         #[rstest]
         #[case(Span::UNKNOWN, Span::UNKNOWN, Span::UNKNOWN)]
         #[case(
             Span {
-                absolute: Some(AbsoluteSpan { start: 1, end: 2 }),
+                absolute: Some(AbsoluteSpan { start: 1, end: 2, start_byte: 1, end_byte: 2 }),
                 relative: RelativeSpan {
                     start: LineAndColumn { line: 4, column: 5 },
                     end: LineAndColumn { line: 6, column: 7 },
                 },
+                provenance: Provenance::NONE,
             },
             Span {
-                absolute: Some(AbsoluteSpan { start: 8, end: 9 }),
+                absolute: Some(AbsoluteSpan { start: 8, end: 9, start_byte: 8, end_byte: 9 }),
                 relative: RelativeSpan {
                     start: LineAndColumn {
                         line: 10,
@@ -494,9 +1770,10 @@ mod test {
                         column: 13,
                     },
                 },
+                provenance: Provenance::NONE,
             },
             Span {
-                absolute: Some(AbsoluteSpan { start: 1, end: 9 }),
+                absolute: Some(AbsoluteSpan { start: 1, end: 9, start_byte: 1, end_byte: 9 }),
                 relative: RelativeSpan {
                     start: LineAndColumn {
                         line: 4,
@@ -507,29 +1784,32 @@ mod test {
                         column: 13,
                     },
                 },
+                provenance: Provenance::NONE,
             },
         )]
         #[case(
             Span {
-                absolute: Some(AbsoluteSpan { start: 1, end: 2 }),
+                absolute: Some(AbsoluteSpan { start: 1, end: 2, start_byte: 1, end_byte: 2 }),
                 relative: RelativeSpan {
                     start: LineAndColumn { line: 4, column: 5 },
                     end: LineAndColumn { line: 6, column: 7 },
                 },
+                provenance: Provenance::NONE,
             },
             Span::UNKNOWN,
             Span {
-                absolute: Some(AbsoluteSpan { start: 1, end: 2 }),
+                absolute: Some(AbsoluteSpan { start: 1, end: 2, start_byte: 1, end_byte: 2 }),
                 relative: RelativeSpan {
                     start: LineAndColumn { line: 4, column: 5 },
                     end: LineAndColumn { line: 6, column: 7 },
                 },
+                provenance: Provenance::NONE,
             },
         )]
         #[case(
             Span::UNKNOWN,
             Span {
-                absolute: Some(AbsoluteSpan { start: 8, end: 9 }),
+                absolute: Some(AbsoluteSpan { start: 8, end: 9, start_byte: 8, end_byte: 9 }),
                 relative: RelativeSpan {
                     start: LineAndColumn {
                         line: 10,
@@ -540,9 +1820,10 @@ mod test {
                         column: 13,
                     },
                 },
+                provenance: Provenance::NONE,
             },
             Span {
-                absolute: Some(AbsoluteSpan { start: 8, end: 9 }),
+                absolute: Some(AbsoluteSpan { start: 8, end: 9, start_byte: 8, end_byte: 9 }),
                 relative: RelativeSpan {
                     start: LineAndColumn {
                         line: 10,
@@ -553,6 +1834,7 @@ mod test {
                         column: 13,
                     },
                 },
+                provenance: Provenance::NONE,
             },
         )]
         fn add(
@@ -569,17 +1851,83 @@ mod test {
         #[case(Span::UNKNOWN, true)]
         #[case(
             Span {
-                absolute: Some(AbsoluteSpan { start: 1, end: 2 }),
+                absolute: Some(AbsoluteSpan { start: 1, end: 2, start_byte: 1, end_byte: 2 }),
                 relative: RelativeSpan {
                     start: LineAndColumn { line: 4, column: 5 },
                     end: LineAndColumn { line: 6, column: 7 },
                 },
+                provenance: Provenance::NONE,
             },
             false,
         )]
         fn is_unknown(#[case] span: Span, #[case] expected: bool) {
             assert_eq!(span.is_unknown(), expected);
         }
+
+        #[test]
+        fn last_char_newline() {
+            let source = "a\nb\n";
+            let mut chars = Chars::new(source);
+            let _ = chars.next();
+            let _ = chars.next();
+            let start = chars.start_token();
+            for _ in chars.by_ref() {}
+            let span = chars.end_token(start);
+            let last = span.last_char(source).unwrap();
+            assert_eq!(
+                format!("{last:#}"),
+                "line 2 column 2 to line 3 column 1"
+            );
+            assert_eq!(last.start_byte(), Some(3));
+        }
+
+        #[test]
+        fn clamp_to_char_boundary() {
+            let span = Span {
+                absolute: Some(AbsoluteSpan {
+                    start: 1,
+                    end: 10,
+                    start_byte: 1,
+                    end_byte: 10,
+                }),
+                relative: RelativeSpan {
+                    start: LineAndColumn { line: 1, column: 2 },
+                    end: LineAndColumn {
+                        line: 1,
+                        column: 11,
+                    },
+                },
+                provenance: Provenance::NONE,
+            };
+            let clamped = span.clamp("££");
+            assert_eq!(clamped.start_byte(), Some(2));
+            assert_eq!(clamped.len_bytes(), Some(2));
+            assert_eq!(clamped.start_position_on_start_line(), Some(2));
+        }
+
+        #[test]
+        fn lines() {
+            let source = "£\n\n£";
+            let mut chars = Chars::new(source);
+            let start = chars.start_token();
+            for _ in chars.by_ref() {}
+            let span = chars.end_token(start);
+            let lines = span.lines(source).collect::<Vec<_>>();
+            assert_eq!(
+                lines
+                    .iter()
+                    .map(|span| {
+                        let start = span.start_byte().unwrap();
+                        &source[start..start + span.len_bytes().unwrap()]
+                    })
+                    .collect::<Vec<_>>(),
+                ["£", "", "£"]
+            );
+            assert_eq!(
+                lines.iter().map(Span::start_line).collect::<Vec<_>>(),
+                [Some(1), Some(2), Some(3)]
+            );
+        }
     }
 
     mod absolute {
@@ -589,12 +1937,12 @@ mod test {
 
         #[rstest]
         #[case(None, None, None)]
-        #[case(Some(AbsoluteSpan { start: 1, end: 2}), None, None)]
-        #[case(None, Some(AbsoluteSpan { start: 3, end: 4}), None)]
+        #[case(Some(AbsoluteSpan { start: 1, end: 2, start_byte: 1, end_byte: 2 }), None, None)]
+        #[case(None, Some(AbsoluteSpan { start: 3, end: 4, start_byte: 3, end_byte: 4 }), None)]
         #[case(
-            Some(AbsoluteSpan { start: 1, end: 2}),
-            Some(AbsoluteSpan { start: 3, end: 4}),
-            Some(AbsoluteSpan { start: 1, end: 4}),
+            Some(AbsoluteSpan { start: 1, end: 2, start_byte: 1, end_byte: 2 }),
+            Some(AbsoluteSpan { start: 3, end: 4, start_byte: 3, end_byte: 4 }),
+            Some(AbsoluteSpan { start: 1, end: 4, start_byte: 1, end_byte: 4 }),
         )]
         fn add(
             #[case] left: Option<AbsoluteSpan>,
@@ -646,5 +1994,25 @@ mod test {
             let right = LineAndColumn { line: 3, column: 4 };
             assert_eq!(right, LineAndColumn::max(left, right));
         }
+
+        #[rstest]
+        #[case(1, 1, Some(0))]
+        #[case(1, 3, Some(2))]
+        #[case(1, 4, None)]
+        #[case(2, 1, Some(3))]
+        #[case(2, 3, Some(6))]
+        #[case(2, 4, None)]
+        #[case(3, 1, Some(7))]
+        #[case(4, 1, None)]
+        #[case(0, 1, None)]
+        #[case(1, 0, None)]
+        fn byte_offset_in(
+            #[case] line: usize,
+            #[case] column: usize,
+            #[case] expected: Option<usize>,
+        ) {
+            let position = LineAndColumn { line, column };
+            assert_eq!(position.byte_offset_in("ab\nc\u{e9}\n"), expected);
+        }
     }
 }
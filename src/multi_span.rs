@@ -0,0 +1,167 @@
+use std::fmt;
+
+use crate::{Span, WithSource};
+
+/// A primary span plus any number of secondary spans, for diagnostics that
+/// point at several disjoint places at once, e.g. every duplicate definition
+/// of a name. Accepted anywhere a diagnostic takes `impl Into<MultiSpan>`,
+/// a plain [Span] converts to a MultiSpan with no secondary spans
+///
+/// Display lists every location, primary first, separated by commas. The
+/// alternate form shows each as a range like [Span]'s alternate Display
+///
+/// ```
+/// # use span::*;
+/// const SRC: &str = "fn f() {}\nfn g() {}\nfn f() {}";
+/// let spans = MultiSpan::new(testing::span_of(SRC, "f(", 1))
+///     .with_secondary(testing::span_of(SRC, "f(", 0));
+/// assert_eq!(spans.to_string(), "line 3 column 4, line 1 column 4");
+/// assert_eq!(
+///     format!("{:#}", spans.aggregate()),
+///     "line 1 column 4 to line 3 column 6"
+/// );
+/// assert_eq!(spans.iter().count(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MultiSpan {
+    primary: Span,
+    secondary: Vec<Span>,
+}
+
+impl MultiSpan {
+    /// MultiSpan with no secondary spans
+    #[must_use]
+    pub fn new(primary: Span) -> Self {
+        Self {
+            primary,
+            secondary: Vec::new(),
+        }
+    }
+
+    /// Add a secondary span
+    #[must_use]
+    pub fn with_secondary(mut self, span: Span) -> Self {
+        self.push(span);
+        self
+    }
+
+    /// Add a secondary span
+    pub fn push(&mut self, span: Span) {
+        self.secondary.push(span);
+    }
+
+    /// The main span of the diagnostic
+    #[must_use]
+    pub fn primary(&self) -> Span {
+        self.primary
+    }
+
+    /// The secondary spans in the order they were added
+    #[must_use]
+    pub fn secondary(&self) -> &[Span] {
+        &self.secondary
+    }
+
+    /// Iterate over the primary span then the secondary spans
+    pub fn iter(&self) -> impl Iterator<Item = Span> + '_ {
+        std::iter::once(self.primary).chain(self.secondary.iter().copied())
+    }
+
+    /// Smallest span covering every span, see [Span::aggregate]. Unknown
+    /// spans are ignored, if every span is unknown the result is
+    /// Span::UNKNOWN
+    #[must_use]
+    pub fn aggregate(&self) -> Span {
+        let known = self
+            .iter()
+            .filter(|span| !span.is_unknown())
+            .collect::<Vec<_>>();
+        if known.is_empty() {
+            Span::UNKNOWN
+        } else {
+            Span::aggregate(&known)
+        }
+    }
+
+    /// Display the primary span with the source lines it covers underlined,
+    /// followed by each secondary span and its source lines, see
+    /// [Span::display_with]. `source` must be the text the spans point into
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "let x = 1;\nlet x = 2;";
+    /// let spans = MultiSpan::new(testing::span_of(SRC, "x", 1))
+    ///     .with_secondary(testing::span_of(SRC, "x", 0));
+    /// assert_eq!(
+    ///     spans.display_with(SRC).to_string(),
+    ///     "line 2 column 5\n\
+    ///      2 | let x = 2;\n  \
+    ///        |     ^\n\
+    ///      line 1 column 5\n\
+    ///      1 | let x = 1;\n  \
+    ///        |     ^\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn display_with<'a>(&'a self, source: &'a str) -> WithSource<'a> {
+        WithSource::new(self.primary, source).with_secondary(&self.secondary)
+    }
+}
+
+impl From<Span> for MultiSpan {
+    fn from(primary: Span) -> Self {
+        MultiSpan::new(primary)
+    }
+}
+
+impl Extend<Span> for MultiSpan {
+    fn extend<T: IntoIterator<Item = Span>>(&mut self, iter: T) {
+        self.secondary.extend(iter);
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for MultiSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, span) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            if f.alternate() {
+                write!(f, "{span:#}")?;
+            } else {
+                write!(f, "{span}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::testing::span_of;
+
+    const SOURCE: &str = "ab\ncd";
+
+    #[test]
+    fn aggregate_ignores_unknown_spans() {
+        let mut spans = MultiSpan::new(Span::UNKNOWN);
+        assert_eq!(spans.aggregate(), Span::UNKNOWN);
+        spans.extend([span_of(SOURCE, "a", 0), span_of(SOURCE, "d", 0)]);
+        assert_eq!(spans.aggregate(), span_of(SOURCE, "ab\ncd", 0));
+    }
+
+    #[test]
+    fn alternate_display() {
+        let spans = MultiSpan::from(span_of(SOURCE, "ab", 0))
+            .with_secondary(span_of(SOURCE, "c", 0));
+        assert_eq!(
+            format!("{spans:#}"),
+            "line 1 column 1 to column 3, line 2 column 1"
+        );
+    }
+}
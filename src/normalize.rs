@@ -0,0 +1,123 @@
+use unicode_normalization::{
+    IsNormalized, UnicodeNormalization as _, char::canonical_combining_class,
+    is_nfc_quick,
+};
+
+use crate::{Chars, SpanMapping};
+
+impl SpanMapping {
+    /// Normalize `source` to NFC (e.g. so identifiers can be compared),
+    /// returning the normalized text and a mapping between it and `source`
+    /// so diagnostics about the normalized text can point at what was
+    /// actually written.
+    ///
+    /// Each run of characters that normalizes independently of its
+    /// neighbours (usually a single character, or a base character and its
+    /// combining marks) is mapped as a unit.
+    ///
+    /// ```
+    /// # use span::*;
+    /// let source = "let cafe\u{301} = 1;";
+    /// let (normalized, mapping) = SpanMapping::nfc(source);
+    /// assert_eq!(normalized, "let caf\u{e9} = 1;");
+    /// let e = testing::span_of(&normalized, "\u{e9}", 0);
+    /// assert_eq!(
+    ///     mapping.original(e),
+    ///     Some(testing::span_of(source, "e\u{301}", 0))
+    /// );
+    /// let ident = testing::span_of(&normalized, "caf\u{e9}", 0);
+    /// let original = mapping.original(ident).unwrap();
+    /// assert_eq!(format!("{original:#}"), "line 1 column 5 to column 10");
+    /// ```
+    #[must_use]
+    pub fn nfc(source: &str) -> (String, SpanMapping) {
+        let mut normalized = String::with_capacity(source.len());
+        let mut segments = Vec::new();
+        let mut start = 0;
+        for (i, c) in source.char_indices().skip(1) {
+            if is_safe_start(c) {
+                segments.push(&source[start..i]);
+                start = i;
+            }
+        }
+        if start < source.len() {
+            segments.push(&source[start..]);
+        }
+        let lens = segments
+            .iter()
+            .map(|segment| {
+                let before = normalized.len();
+                normalized.extend(segment.nfc());
+                (
+                    segment.chars().count(),
+                    normalized[before..].chars().count(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut original = Chars::new(source);
+        let mut generated = Chars::new(normalized.as_str());
+        let pairs = lens
+            .into_iter()
+            .map(|(original_len, generated_len)| {
+                let original_start = original.start_token();
+                let generated_start = generated.start_token();
+                let _ = original.advance_by(original_len);
+                let _ = generated.advance_by(generated_len);
+                (
+                    original.end_token(original_start),
+                    generated.end_token(generated_start),
+                )
+            })
+            .collect();
+        (normalized, pairs)
+    }
+}
+
+/// Whether NFC can't combine `c` with anything before it, so text can be
+/// split before `c` and the parts normalized separately
+fn is_safe_start(c: char) -> bool {
+    canonical_combining_class(c) == 0
+        && is_nfc_quick(std::iter::once(c)) == IsNormalized::Yes
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::testing::span_of;
+
+    #[rstest]
+    // Hangul jamo compose even though they are all starters
+    #[case(
+        "x\u{1100}\u{1161}\u{11a8}y",
+        "x\u{ac01}y",
+        "\u{ac01}",
+        "\u{1100}\u{1161}\u{11a8}"
+    )]
+    // Combining marks are reordered and composed
+    #[case("a\u{323}\u{302}b", "\u{1ead}b", "\u{1ead}", "a\u{323}\u{302}")]
+    // Singletons decompose
+    #[case("\u{2126}\n", "\u{3a9}\n", "\u{3a9}", "\u{2126}")]
+    fn maps_back(
+        #[case] source: &str,
+        #[case] expected: &str,
+        #[case] needle: &str,
+        #[case] original: &str,
+    ) {
+        let (normalized, mapping) = SpanMapping::nfc(source);
+        assert_eq!(normalized, expected);
+        assert_eq!(
+            mapping.original(span_of(&normalized, needle, 0)),
+            Some(span_of(source, original, 0))
+        );
+        let last = normalized.chars().last().unwrap().to_string();
+        assert_eq!(
+            mapping.original(span_of(&normalized, &last, 0)),
+            Some(span_of(source, &last, 0))
+        );
+    }
+}
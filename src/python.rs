@@ -0,0 +1,169 @@
+//! Python bindings, for generating and checking spans from Python scripts
+//! with exactly the behaviour of this crate. The classes are thin wrappers
+//! around [Span] and [Chars] and are registered by [span](fn@span)
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{Chars, Span, TokenHandle};
+
+/// Python wrapper around [Span]
+#[pyclass(name = "Span", module = "span", frozen, eq, hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PySpan(pub Span);
+
+#[cfg_attr(coverage, coverage(off))]
+#[pymethods]
+impl PySpan {
+    /// See [Span::UNKNOWN]
+    #[classattr]
+    const UNKNOWN: PySpan = PySpan(Span::UNKNOWN);
+
+    /// See [Span::aggregate], raises ValueError for an empty list rather than
+    /// panicking
+    #[staticmethod]
+    fn aggregate(spans: Vec<PySpan>) -> PyResult<PySpan> {
+        if spans.is_empty() {
+            return Err(PyValueError::new_err("no spans to aggregate"));
+        }
+        let spans = spans.into_iter().map(|span| span.0).collect::<Vec<_>>();
+        Ok(PySpan(Span::aggregate(&spans)))
+    }
+
+    /// The span's Display, `alternate` selects the `{:#}` form
+    #[pyo3(signature = (alternate = false))]
+    fn format(&self, alternate: bool) -> String {
+        if alternate {
+            format!("{:#}", self.0)
+        } else {
+            format!("{}", self.0)
+        }
+    }
+
+    fn __str__(&self) -> String {
+        self.format(false)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+
+    /// See [Span::is_unknown]
+    fn is_unknown(&self) -> bool {
+        self.0.is_unknown()
+    }
+
+    /// See [Span::start_line_and_column]
+    #[getter]
+    fn start(&self) -> Option<(usize, usize)> {
+        self.0
+            .start_line_and_column()
+            .map(|lc| (lc.line, lc.column))
+    }
+
+    /// See [Span::end_line_and_column]
+    #[getter]
+    fn end(&self) -> Option<(usize, usize)> {
+        self.0.end_line_and_column().map(|lc| (lc.line, lc.column))
+    }
+
+    /// The byte range covered by the span as `(start, end)`
+    #[getter]
+    fn byte_range(&self) -> Option<(usize, usize)> {
+        let start = self.0.start_byte()?;
+        Some((start, start + self.0.len_bytes()?))
+    }
+}
+
+/// Python wrapper around [TokenHandle], can only be passed to
+/// [PyChars::end_token] once
+#[pyclass(name = "TokenHandle", module = "span", unsendable)]
+#[expect(missing_debug_implementations)]
+pub struct PyTokenHandle(Option<TokenHandle>);
+
+/// Python wrapper around [Chars], iterating yields each character
+#[pyclass(name = "Chars", module = "span", unsendable)]
+#[expect(missing_debug_implementations)]
+pub struct PyChars(pub Chars);
+
+#[cfg_attr(coverage, coverage(off))]
+#[pymethods]
+impl PyChars {
+    #[new]
+    fn new(source: String) -> Self {
+        PyChars(Chars::new(source))
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<char> {
+        self.0.next()
+    }
+
+    /// See [Chars::peek]
+    fn peek(&mut self) -> Option<char> {
+        self.0.peek()
+    }
+
+    /// See [Chars::skip_whitespace]
+    fn skip_whitespace(&mut self) -> Option<char> {
+        self.0.skip_whitespace()
+    }
+
+    /// See [Chars::start_token]
+    fn start_token(&self) -> PyTokenHandle {
+        PyTokenHandle(Some(self.0.start_token()))
+    }
+
+    /// See [Chars::end_token], raises ValueError if `handle` has already been
+    /// used
+    fn end_token(
+        &mut self,
+        mut handle: PyRefMut<'_, PyTokenHandle>,
+    ) -> PyResult<PySpan> {
+        let handle = handle
+            .0
+            .take()
+            .ok_or_else(|| PyValueError::new_err("token already ended"))?;
+        Ok(PySpan(self.0.end_token(handle)))
+    }
+}
+
+/// The `span` Python module
+///
+/// # Errors
+///
+/// If a class can't be added to the module
+#[pymodule]
+pub fn span(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PySpan>()?;
+    module.add_class::<PyTokenHandle>()?;
+    module.add_class::<PyChars>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::testing::span_of;
+
+    #[test]
+    fn matches_rust() {
+        const SRC: &str = "ab\ncd";
+        let spans = [span_of(SRC, "b", 0), span_of(SRC, "c", 0)];
+        let aggregated =
+            PySpan::aggregate(spans.iter().copied().map(PySpan).collect())
+                .unwrap();
+        assert_eq!(aggregated.0, Span::aggregate(&spans));
+        assert_eq!(aggregated.format(true), format!("{:#}", aggregated.0));
+        assert_eq!(aggregated.start(), Some((1, 2)));
+        assert_eq!(aggregated.end(), Some((2, 2)));
+        assert_eq!(aggregated.byte_range(), Some((1, 4)));
+        assert!(PySpan::aggregate(Vec::new()).is_err());
+        assert_eq!(PySpan::UNKNOWN.__str__(), "???");
+    }
+}
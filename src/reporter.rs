@@ -0,0 +1,334 @@
+use std::fmt::{self, Write as _};
+
+use crate::{MultiSpan, Span, SpannedError, snippet::write_snippet};
+
+/// How serious a reported diagnostic is, see [ErrorReporter]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Extra information, e.g. where something was previously defined
+    Note,
+    /// Something suspicious that doesn't stop compilation
+    Warning,
+    /// Something that stops compilation
+    Error,
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Note => write!(f, "note"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A diagnostic collected by [ErrorReporter]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Reported {
+    /// How serious the diagnostic is
+    pub severity: Severity,
+    /// The source the diagnostic is about
+    pub span: Span,
+    /// Other places the diagnostic points at, see [MultiSpan]
+    pub secondary: Vec<Span>,
+    /// The message, starting with the span
+    pub message: String,
+    /// Other relevant parts of the source, see [SpannedError::labels]
+    pub labels: Vec<(Span, String)>,
+}
+
+/// Returned by [ErrorReporter] when the error limit set with
+/// [ErrorReporter::with_max_errors] is reached, compilation should stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TooManyErrors {
+    /// The configured limit
+    pub limit: usize,
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for TooManyErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "aborting after {} errors", self.limit)
+    }
+}
+
+impl std::error::Error for TooManyErrors {}
+
+/// Collects diagnostics of all severities so compilation can carry on after
+/// a problem and report everything at the end, optionally giving up once a
+/// number of errors has been reached
+///
+/// ```
+/// # use span::*;
+/// const SRC: &str = "let x = y;\nlet z = 0x;";
+/// let mut reporter = ErrorReporter::new().with_max_errors(2);
+/// let z = testing::span_of(SRC, "z", 0);
+/// reporter.report(Severity::Warning, z, "unused variable")?;
+/// let y = testing::span_of(SRC, "y", 0);
+/// reporter.report(Severity::Error, y, "unknown variable")?;
+/// let mut chars = Chars::new(SRC);
+/// for _ in (&mut chars).take(19) {}
+/// let Some(Err(error)) = chars.scan_integer() else { panic!() };
+/// let abort = reporter.report_error(&error).unwrap_err();
+/// assert_eq!(abort.to_string(), "aborting after 2 errors");
+///
+/// assert_eq!(reporter.count(Severity::Error), 2);
+/// assert_eq!(reporter.summary().to_string(), "2 errors, 1 warning");
+/// assert_eq!(
+///     reporter.render(SRC),
+///     "warning: line 2 column 5: unused variable\n\
+///      2 | let z = 0x;\n  \
+///        |     ^\n\
+///      error: line 1 column 9: unknown variable\n\
+///      1 | let x = y;\n  \
+///        |         ^\n\
+///      error: line 2 column 9: expected digits\n\
+///      2 | let z = 0x;\n  \
+///        |         ^^\n\
+///      2 errors, 1 warning\n"
+/// );
+/// # Ok::<(), TooManyErrors>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ErrorReporter {
+    reported: Vec<Reported>,
+    max_errors: Option<usize>,
+}
+
+impl ErrorReporter {
+    /// Reporter with no error limit
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give up once `max` errors have been reported, the report that reaches
+    /// the limit returns [TooManyErrors]. Warnings and notes never count
+    /// towards the limit
+    #[must_use]
+    pub fn with_max_errors(self, max: usize) -> Self {
+        Self {
+            max_errors: Some(max),
+            ..self
+        }
+    }
+
+    /// Record a diagnostic, the span is prefixed to `message`. `span` can be
+    /// a [MultiSpan] to point at several places, only the primary span is
+    /// prefixed to the message
+    ///
+    /// # Errors
+    /// If this is an error and it reaches the limit set with
+    /// [ErrorReporter::with_max_errors]. The diagnostic is still recorded
+    pub fn report(
+        &mut self,
+        severity: Severity,
+        span: impl Into<MultiSpan>,
+        message: impl fmt::Display,
+    ) -> Result<(), TooManyErrors> {
+        let span = span.into();
+        self.push(Reported {
+            severity,
+            span: span.primary(),
+            secondary: span.secondary().to_vec(),
+            message: format!("{}: {message}", span.primary()),
+            labels: Vec::new(),
+        })
+    }
+
+    /// Record `error` along with its labels, using its Display as the
+    /// message
+    ///
+    /// # Errors
+    /// As [ErrorReporter::report]
+    pub fn report_error(
+        &mut self,
+        error: &(impl SpannedError + ?Sized),
+    ) -> Result<(), TooManyErrors> {
+        self.push(Reported {
+            severity: Severity::Error,
+            span: error.span(),
+            secondary: Vec::new(),
+            message: error.to_string(),
+            labels: error.labels(),
+        })
+    }
+
+    fn push(&mut self, reported: Reported) -> Result<(), TooManyErrors> {
+        let severity = reported.severity;
+        self.reported.push(reported);
+        match self.max_errors {
+            Some(limit)
+                if severity == Severity::Error
+                    && self.count(Severity::Error) >= limit =>
+            {
+                Err(TooManyErrors { limit })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Number of diagnostics reported with `severity`
+    #[must_use]
+    pub fn count(&self, severity: Severity) -> usize {
+        self.reported
+            .iter()
+            .filter(|reported| reported.severity == severity)
+            .count()
+    }
+
+    /// Whether any errors have been reported
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        self.count(Severity::Error) > 0
+    }
+
+    /// Everything reported so far, in the order it was reported
+    #[must_use]
+    pub fn reported(&self) -> &[Reported] {
+        &self.reported
+    }
+
+    /// Counts of each severity for display, e.g. `3 errors, 2 warnings`.
+    /// Severities with no diagnostics are left out, if nothing was reported
+    /// at all this is `no errors`
+    #[must_use]
+    pub fn summary(&self) -> Summary {
+        Summary {
+            errors: self.count(Severity::Error),
+            warnings: self.count(Severity::Warning),
+            notes: self.count(Severity::Note),
+        }
+    }
+
+    /// Render every diagnostic with the source it points at, in the order
+    /// they were reported, followed by the summary. `source` must be the
+    /// text the spans point into
+    #[must_use]
+    pub fn render(&self, source: &str) -> String {
+        let mut rendered = String::new();
+        for reported in &self.reported {
+            let _ = writeln!(
+                rendered,
+                "{}: {}",
+                reported.severity, reported.message
+            );
+            let _ = write_snippet(&mut rendered, source, reported.span);
+            for span in &reported.secondary {
+                let _ = writeln!(rendered, "{span:#}");
+                let _ = write_snippet(&mut rendered, source, *span);
+            }
+            for (span, message) in &reported.labels {
+                let _ = writeln!(rendered, "{span:#}: {message}");
+                let _ = write_snippet(&mut rendered, source, *span);
+            }
+        }
+        let _ = writeln!(rendered, "{}", self.summary());
+        rendered
+    }
+}
+
+/// See [ErrorReporter::summary]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Summary {
+    /// Number of errors
+    pub errors: usize,
+    /// Number of warnings
+    pub warnings: usize,
+    /// Number of notes
+    pub notes: usize,
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = [
+            (self.errors, "error"),
+            (self.warnings, "warning"),
+            (self.notes, "note"),
+        ];
+        let mut first = true;
+        for (count, name) in counts.into_iter().filter(|(n, _)| *n > 0) {
+            if !first {
+                write!(f, ", ")?;
+            }
+            first = false;
+            let plural = if count == 1 { "" } else { "s" };
+            write!(f, "{count} {name}{plural}")?;
+        }
+        if first {
+            write!(f, "no errors")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(0, 0, 0, "no errors")]
+    #[case(1, 0, 0, "1 error")]
+    #[case(0, 2, 1, "2 warnings, 1 note")]
+    #[case(3, 1, 2, "3 errors, 1 warning, 2 notes")]
+    fn summary(
+        #[case] errors: usize,
+        #[case] warnings: usize,
+        #[case] notes: usize,
+        #[case] expected: &str,
+    ) {
+        let summary = Summary {
+            errors,
+            warnings,
+            notes,
+        };
+        assert_eq!(summary.to_string(), expected);
+    }
+
+    #[test]
+    fn warnings_dont_count_towards_limit() {
+        let mut reporter = ErrorReporter::new().with_max_errors(1);
+        for _ in 0..3 {
+            assert_eq!(
+                reporter.report(Severity::Warning, Span::UNKNOWN, "w"),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            reporter.report(Severity::Error, Span::UNKNOWN, "e"),
+            Err(TooManyErrors { limit: 1 })
+        );
+        assert_eq!(reporter.reported().len(), 4);
+        assert!(reporter.has_errors());
+    }
+
+    #[test]
+    fn renders_secondary_spans() {
+        const SRC: &str = "a = 1
+a = 2";
+        let mut reporter = ErrorReporter::new();
+        let spans = MultiSpan::new(crate::testing::span_of(SRC, "a", 1))
+            .with_secondary(crate::testing::span_of(SRC, "a", 0));
+        assert_eq!(
+            reporter.report(Severity::Error, spans, "duplicate"),
+            Ok(())
+        );
+        assert_eq!(
+            reporter.render(SRC),
+            "error: line 2 column 1: duplicate\n\
+             2 | a = 2\n  \
+               | ^\n\
+             line 1 column 1\n\
+             1 | a = 1\n  \
+               | ^\n\
+             1 error\n"
+        );
+    }
+}
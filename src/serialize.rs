@@ -0,0 +1,252 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+
+use crate::{AbsoluteSpan, LineAndColumn, Provenance, RelativeSpan, Span};
+
+/// Layout used for human readable formats
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Span")]
+struct Flat {
+    start: usize,
+    end: usize,
+    start_byte: usize,
+    end_byte: usize,
+    start_line: usize,
+    start_col: usize,
+    end_line: usize,
+    end_col: usize,
+}
+
+/// Layout used for everything else, fields are in the same order as [Flat]
+type Compact = (usize, usize, usize, usize, usize, usize, usize, usize);
+
+impl Flat {
+    /// [Span::UNKNOWN] has no flat representation, it is serialized as `None`
+    fn new(span: Span) -> Option<Self> {
+        let AbsoluteSpan {
+            start,
+            end,
+            start_byte,
+            end_byte,
+        } = span.absolute?;
+        let RelativeSpan {
+            start:
+                LineAndColumn {
+                    line: start_line,
+                    column: start_col,
+                },
+            end:
+                LineAndColumn {
+                    line: end_line,
+                    column: end_col,
+                },
+        } = span.relative;
+        Some(Flat {
+            start,
+            end,
+            start_byte,
+            end_byte,
+            start_line,
+            start_col,
+            end_line,
+            end_col,
+        })
+    }
+}
+
+impl From<Flat> for Span {
+    fn from(flat: Flat) -> Self {
+        Span {
+            absolute: Some(AbsoluteSpan {
+                start: flat.start,
+                end: flat.end,
+                start_byte: flat.start_byte,
+                end_byte: flat.end_byte,
+            }),
+            relative: RelativeSpan {
+                start: LineAndColumn {
+                    line: flat.start_line,
+                    column: flat.start_col,
+                },
+                end: LineAndColumn {
+                    line: flat.end_line,
+                    column: flat.end_col,
+                },
+            },
+            provenance: Provenance::NONE,
+        }
+    }
+}
+
+impl From<Flat> for Compact {
+    fn from(flat: Flat) -> Self {
+        (
+            flat.start,
+            flat.end,
+            flat.start_byte,
+            flat.end_byte,
+            flat.start_line,
+            flat.start_col,
+            flat.end_line,
+            flat.end_col,
+        )
+    }
+}
+
+impl From<Compact> for Flat {
+    fn from(
+        (
+            start,
+            end,
+            start_byte,
+            end_byte,
+            start_line,
+            start_col,
+            end_line,
+            end_col,
+        ): Compact,
+    ) -> Self {
+        Flat {
+            start,
+            end,
+            start_byte,
+            end_byte,
+            start_line,
+            start_col,
+            end_line,
+            end_col,
+        }
+    }
+}
+
+impl Serialize for Span {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let flat = Flat::new(*self);
+        if serializer.is_human_readable() {
+            flat.serialize(serializer)
+        } else {
+            flat.map(Compact::from).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Span {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let flat = if deserializer.is_human_readable() {
+            Option::<Flat>::deserialize(deserializer)?
+        } else {
+            Option::<Compact>::deserialize(deserializer)?.map(Flat::from)
+        };
+        let Some(flat) = flat else {
+            return Ok(Span::UNKNOWN);
+        };
+        let span = Span::from(flat);
+        span.validate().map_err(D::Error::custom)?;
+        Ok(span)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use rstest::rstest;
+    use serde_test::{
+        Configure as _, Token, assert_de_tokens_error, assert_tokens,
+    };
+
+    use super::*;
+
+    const SPAN: Span = Span {
+        absolute: Some(AbsoluteSpan {
+            start: 1,
+            end: 9,
+            start_byte: 2,
+            end_byte: 12,
+        }),
+        relative: RelativeSpan {
+            start: LineAndColumn { line: 1, column: 2 },
+            end: LineAndColumn { line: 3, column: 4 },
+        },
+        provenance: Provenance::NONE,
+    };
+
+    #[test]
+    fn readable() {
+        assert_tokens(
+            &SPAN.readable(),
+            &[
+                Token::Some,
+                Token::Struct {
+                    name: "Span",
+                    len: 8,
+                },
+                Token::Str("start"),
+                Token::U64(1),
+                Token::Str("end"),
+                Token::U64(9),
+                Token::Str("start_byte"),
+                Token::U64(2),
+                Token::Str("end_byte"),
+                Token::U64(12),
+                Token::Str("start_line"),
+                Token::U64(1),
+                Token::Str("start_col"),
+                Token::U64(2),
+                Token::Str("end_line"),
+                Token::U64(3),
+                Token::Str("end_col"),
+                Token::U64(4),
+                Token::StructEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn compact() {
+        assert_tokens(
+            &SPAN.compact(),
+            &[
+                Token::Some,
+                Token::Tuple { len: 8 },
+                Token::U64(1),
+                Token::U64(9),
+                Token::U64(2),
+                Token::U64(12),
+                Token::U64(1),
+                Token::U64(2),
+                Token::U64(3),
+                Token::U64(4),
+                Token::TupleEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        assert_tokens(&Span::UNKNOWN.readable(), &[Token::None]);
+        assert_tokens(&Span::UNKNOWN.compact(), &[Token::None]);
+    }
+
+    #[rstest]
+    #[case([5, 4, 5, 4, 1, 1, 1, 1], "span ends before it starts")]
+    #[case([1, 4, 5, 4, 1, 1, 1, 4], "span ends before it starts")]
+    #[case([1, 4, 1, 4, 2, 1, 1, 5], "span ends before it starts")]
+    #[case(
+        [1, 4, 1, 4, 0, 1, 1, 5],
+        "span lines and columns are 1 indexed, found 0"
+    )]
+    #[case(
+        [1, 4, 1, 4, usize::MAX, usize::MAX, usize::MAX, usize::MAX],
+        "span has an absolute position but no line and column"
+    )]
+    fn invalid(#[case] fields: [usize; 8], #[case] error: &str) {
+        let mut stream = vec![Token::Some, Token::Tuple { len: 8 }];
+        stream.extend(fields.map(|field| Token::U64(field as u64)));
+        stream.push(Token::TupleEnd);
+        assert_de_tokens_error::<serde_test::Compact<Span>>(&stream, error);
+    }
+}
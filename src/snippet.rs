@@ -0,0 +1,71 @@
+use std::fmt;
+
+use unicode_width::UnicodeWidthChar as _;
+
+use crate::Span;
+
+/// Write the source lines `span` covers, each followed by a line underlining
+/// the part of it inside the span. Empty spans get a single caret, unknown
+/// spans and spans that don't fit `source` write nothing. The underline is
+/// measured in display width so it lines up under wide characters (CJK,
+/// emoji) the way a terminal shows them
+pub(crate) fn write_snippet(
+    out: &mut impl fmt::Write,
+    source: &str,
+    span: Span,
+) -> fmt::Result {
+    let width = span.end_line().unwrap_or(0).to_string().len();
+    for piece in span.lines(source) {
+        let Some(absolute) = piece.absolute else {
+            continue;
+        };
+        let line_start = source[..absolute.start_byte]
+            .rfind('\n')
+            .map_or(0, |nl| nl + 1);
+        let line_end = source[absolute.start_byte..]
+            .find('\n')
+            .map_or(source.len(), |nl| absolute.start_byte + nl);
+        let indent = source[line_start..absolute.start_byte]
+            .chars()
+            .map(|c| match c {
+                '\t' => String::from('\t'),
+                c => " ".repeat(c.width().unwrap_or(0)),
+            })
+            .collect::<String>();
+        let underline = source[absolute.start_byte..absolute.end_byte]
+            .chars()
+            .map(|c| if c == '\t' { 1 } else { c.width().unwrap_or(0) })
+            .sum::<usize>();
+        let carets = "^".repeat(underline.max(1));
+        let line = piece.relative.start.line;
+        writeln!(out, "{line:>width$} | {}", &source[line_start..line_end])?;
+        writeln!(out, "{:width$} | {indent}{carets}", "")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::testing::span_of;
+
+    #[rstest]
+    #[case("let 名前 = x;", "x", "1 | let 名前 = x;\n  |            ^\n")]
+    #[case("let 名前 = x;", "名前", "1 | let 名前 = x;\n  |     ^^^^\n")]
+    #[case("f(😀, y)", "y", "1 | f(😀, y)\n  |       ^\n")]
+    #[case("\tx", "x", "1 | \tx\n  | \t^\n")]
+    fn aligns_with_display_width(
+        #[case] source: &str,
+        #[case] target: &str,
+        #[case] expected: &str,
+    ) {
+        let mut rendered = String::new();
+        write_snippet(&mut rendered, source, span_of(source, target, 0))
+            .unwrap();
+        assert_eq!(rendered, expected);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{Span, SpanTree};
+
+/// Correspondence between spans in some source and spans in text generated
+/// from it (by a pretty printer, macro expander, transpiler...), so
+/// diagnostics about the generated text can be reported against the source
+///
+/// Built from (original, generated) pairs. Resolving a span in either
+/// direction aggregates the counterparts of every pair it overlaps, mappings
+/// for successive stages can be chained with [SpanMapping::then].
+///
+/// ```
+/// # use span::*;
+/// let source = "a+b";
+/// let pretty = "a + b";
+/// let wrapped = "(a + b)";
+/// let pair = |from, to, needle| {
+///     (testing::span_of(from, needle, 0), testing::span_of(to, needle, 0))
+/// };
+///
+/// let first = ["a", "+", "b"]
+///     .into_iter()
+///     .map(|needle| pair(source, pretty, needle))
+///     .collect::<SpanMapping>();
+/// let second = [pair(pretty, wrapped, "a + b")]
+///     .into_iter()
+///     .collect::<SpanMapping>();
+/// let both = first.then(&second);
+///
+/// let b = testing::span_of(wrapped, "b", 0);
+/// assert_eq!(both.original(b), Some(testing::span_of(source, "a+b", 0)));
+/// assert_eq!(
+///     both.generated(testing::span_of(source, "+", 0)),
+///     Some(testing::span_of(wrapped, "a + b", 0))
+/// );
+/// assert_eq!(both.original(testing::span_of(wrapped, "(", 0)), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpanMapping {
+    /// Keyed by generated span
+    to_original: SpanTree<Span>,
+    /// Keyed by original span
+    to_generated: SpanTree<Span>,
+}
+
+impl SpanMapping {
+    /// Build a mapping from (original, generated) pairs. Pairs where either
+    /// side is Span::UNKNOWN are ignored
+    pub fn new(pairs: impl IntoIterator<Item = (Span, Span)>) -> Self {
+        let pairs = pairs
+            .into_iter()
+            .filter(|(original, generated)| {
+                !original.is_unknown() && !generated.is_unknown()
+            })
+            .collect::<Vec<_>>();
+        Self {
+            to_original: pairs
+                .iter()
+                .map(|&(original, generated)| (generated, original))
+                .collect(),
+            to_generated: pairs.into_iter().collect(),
+        }
+    }
+
+    /// Span of the original text that produced `generated`, or None if no
+    /// part of it came from the original
+    #[must_use]
+    pub fn original(&self, generated: Span) -> Option<Span> {
+        resolve(&self.to_original, generated)
+    }
+
+    /// Span of the generated text produced from `original`, or None if no
+    /// part of it made it into the output
+    #[must_use]
+    pub fn generated(&self, original: Span) -> Option<Span> {
+        resolve(&self.to_generated, original)
+    }
+
+    /// Compose with the mapping for a following stage, whose original text is
+    /// this mapping's generated text. The result maps directly from this
+    /// mapping's original text to `next`'s generated text
+    #[must_use]
+    pub fn then(&self, next: &SpanMapping) -> SpanMapping {
+        next.to_original
+            .iter()
+            .filter_map(|(generated, intermediate)| {
+                Some((self.original(*intermediate)?, generated))
+            })
+            .collect()
+    }
+}
+
+impl FromIterator<(Span, Span)> for SpanMapping {
+    fn from_iter<T: IntoIterator<Item = (Span, Span)>>(iter: T) -> Self {
+        Self::new(iter)
+    }
+}
+
+fn resolve(tree: &SpanTree<Span>, span: Span) -> Option<Span> {
+    let found = tree
+        .overlapping(span)
+        .into_iter()
+        .map(|(_, counterpart)| *counterpart)
+        .collect::<Vec<_>>();
+    if found.is_empty() {
+        return None;
+    }
+    Some(Span::aggregate(&found))
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::testing::span_of;
+
+    const SOURCE: &str = "let x=1;let y=2;";
+    const PRETTY: &str = "let x = 1;\nlet y = 2;\n";
+
+    fn mapping() -> SpanMapping {
+        ["let x=1;", "let y=2;"]
+            .into_iter()
+            .zip(["let x = 1;", "let y = 2;"])
+            .map(|(original, generated)| {
+                (span_of(SOURCE, original, 0), span_of(PRETTY, generated, 0))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resolves_across_lines() {
+        let mapping = mapping();
+        assert_eq!(
+            mapping.original(span_of(PRETTY, "1;\nlet", 0)),
+            Some(span_of(SOURCE, SOURCE, 0))
+        );
+        assert_eq!(
+            mapping.generated(span_of(SOURCE, "y", 0)),
+            Some(span_of(PRETTY, "let y = 2;", 0))
+        );
+    }
+
+    #[test]
+    fn unmapped() {
+        let mapping = mapping();
+        assert_eq!(mapping.original(span_of(PRETTY, "\n", 1)), None);
+        assert_eq!(mapping.original(Span::UNKNOWN), None);
+        assert_eq!(
+            mapping
+                .then(&SpanMapping::new([]))
+                .generated(span_of(SOURCE, "x", 0)),
+            None
+        );
+    }
+}
@@ -0,0 +1,372 @@
+use std::cmp::Ordering;
+
+use crate::{AbsoluteSpan, LineAndColumn, Provenance, RelativeSpan, Span};
+
+/// Set of regions of a source file, stored as a sorted list of disjoint spans
+///
+/// Touching or overlapping spans are merged as they are added so iterating
+/// the set yields maximal covered ranges. Empty spans and Span::UNKNOWN cover
+/// nothing so they are ignored.
+///
+/// ```
+/// # use span::*;
+/// let source = "let x = y + z;";
+/// let statement = testing::span_of(source, "let x = y + z", 0);
+/// let x = testing::span_of(source, "x", 0);
+/// let y = testing::span_of(source, "y", 0);
+/// let z = testing::span_of(source, "z", 0);
+///
+/// let names = [x, y, z].into_iter().collect::<SpanSet>();
+/// let rest = SpanSet::from(statement).difference(&names);
+/// assert_eq!(
+///     rest.iter().map(|s| format!("{s:#}")).collect::<Vec<_>>(),
+///     [
+///         "line 1 column 1 to column 5",
+///         "line 1 column 6 to column 9",
+///         "line 1 column 10 to column 13",
+///     ]
+/// );
+/// assert!(rest.contains(0));
+/// assert!(!rest.contains(4));
+/// assert_eq!(rest.union(&names), SpanSet::from(statement));
+/// assert_eq!(rest.intersection(&names), SpanSet::new());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SpanSet {
+    spans: Vec<Span>,
+}
+
+impl SpanSet {
+    /// Empty set
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the region covered by `span` to the set
+    pub fn insert(&mut self, span: Span) {
+        if span.len_chars().is_some_and(|len| len > 0) {
+            self.spans.push(span);
+            Span::coalesce(&mut self.spans);
+        }
+    }
+
+    /// Check if the set contains no regions
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Check if the byte at `offset` is covered by the set
+    #[must_use]
+    pub fn contains(&self, offset: usize) -> bool {
+        self.spans
+            .binary_search_by(|span| {
+                let AbsoluteSpan {
+                    start_byte,
+                    end_byte,
+                    ..
+                } = absolute(span);
+                if end_byte <= offset {
+                    Ordering::Less
+                } else if start_byte > offset {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    /// Iterate over the maximal covered ranges in source order
+    pub fn iter(&self) -> impl Iterator<Item = Span> + '_ {
+        self.spans.iter().copied()
+    }
+
+    /// Fraction of the first `total_len` bytes of the source that the set
+    /// covers, between 0 and 1. An empty source counts as fully covered
+    ///
+    /// ```
+    /// # use span::*;
+    /// let source = "let x = 1;";
+    /// let tokens = ["let", "x", "=", "1", ";"]
+    ///     .into_iter()
+    ///     .map(|token| testing::span_of(source, token, 0))
+    ///     .collect::<SpanSet>();
+    /// assert_eq!(tokens.coverage(source.len()), 0.7);
+    /// assert_eq!(SpanSet::new().coverage(0), 1.0);
+    /// ```
+    #[must_use]
+    #[expect(clippy::cast_precision_loss)]
+    pub fn coverage(&self, total_len: usize) -> f64 {
+        if total_len == 0 {
+            return 1.0;
+        }
+        let covered = self
+            .spans
+            .iter()
+            .map(|span| {
+                let absolute = absolute(span);
+                absolute.end_byte.min(total_len)
+                    - absolute.start_byte.min(total_len)
+            })
+            .sum::<usize>();
+        covered as f64 / total_len as f64
+    }
+
+    /// Regions of `source` the set doesn't cover, in source order. `source`
+    /// must be the text the spans point into, it is needed to find the
+    /// position of its end
+    ///
+    /// ```
+    /// # use span::*;
+    /// let source = "let x\n= 1;";
+    /// let tokens = ["let", "x", "=", "1"]
+    ///     .into_iter()
+    ///     .map(|token| testing::span_of(source, token, 0))
+    ///     .collect::<SpanSet>();
+    /// assert_eq!(
+    ///     tokens.gaps(source).map(|s| format!("{s:#}")).collect::<Vec<_>>(),
+    ///     [
+    ///         "line 1 column 4",
+    ///         "line 1 column 6 to line 2 column 1",
+    ///         "line 2 column 2",
+    ///         "line 2 column 4",
+    ///     ]
+    /// );
+    /// ```
+    pub fn gaps(&self, source: &str) -> impl Iterator<Item = Span> {
+        let end = Span::from_byte_range(source, source.len(), source.len());
+        let mut gaps = Vec::new();
+        let mut start = Boundary {
+            loc: 0,
+            byte: 0,
+            position: LineAndColumn { line: 1, column: 1 },
+        };
+        for span in &self.spans {
+            let next = Boundary::start(span);
+            if start.byte < next.byte {
+                gaps.push(Boundary::span(start, next));
+            }
+            start = Boundary::end(span);
+        }
+        let end = Boundary::end(&end);
+        if start.byte < end.byte {
+            gaps.push(Boundary::span(start, end));
+        }
+        gaps.into_iter()
+    }
+
+    /// Regions covered by either set
+    #[must_use]
+    pub fn union(&self, other: &SpanSet) -> SpanSet {
+        self.iter().chain(other.iter()).collect()
+    }
+
+    /// Regions covered by both sets
+    #[must_use]
+    pub fn intersection(&self, other: &SpanSet) -> SpanSet {
+        let mut spans = Vec::new();
+        let (mut left, mut right) = (self.spans.iter(), other.spans.iter());
+        let (mut a, mut b) = (left.next(), right.next());
+        while let (Some(x), Some(y)) = (a, b) {
+            let start = Boundary::start(x).max(Boundary::start(y));
+            let end = Boundary::end(x).min(Boundary::end(y));
+            if start.byte < end.byte {
+                spans.push(Boundary::span(start, end));
+            }
+            if absolute(x).end_byte <= absolute(y).end_byte {
+                a = left.next();
+            } else {
+                b = right.next();
+            }
+        }
+        SpanSet { spans }
+    }
+
+    /// Regions covered by this set but not by `other`
+    #[must_use]
+    pub fn difference(&self, other: &SpanSet) -> SpanSet {
+        let mut spans = Vec::new();
+        for span in &self.spans {
+            let mut start = Boundary::start(span);
+            let end = Boundary::end(span);
+            for hole in &other.spans {
+                let (hole_start, hole_end) =
+                    (Boundary::start(hole), Boundary::end(hole));
+                if hole_end.byte <= start.byte {
+                    continue;
+                }
+                if hole_start.byte >= end.byte {
+                    break;
+                }
+                if start.byte < hole_start.byte {
+                    spans.push(Boundary::span(start, hole_start));
+                }
+                start = start.max(hole_end);
+            }
+            if start.byte < end.byte {
+                spans.push(Boundary::span(start, end));
+            }
+        }
+        SpanSet { spans }
+    }
+}
+
+impl From<Span> for SpanSet {
+    fn from(span: Span) -> Self {
+        let mut set = SpanSet::new();
+        set.insert(span);
+        set
+    }
+}
+
+impl FromIterator<Span> for SpanSet {
+    fn from_iter<T: IntoIterator<Item = Span>>(iter: T) -> Self {
+        let mut spans = iter
+            .into_iter()
+            .filter(|span| span.len_chars().is_some_and(|len| len > 0))
+            .collect();
+        Span::coalesce(&mut spans);
+        SpanSet { spans }
+    }
+}
+
+impl Extend<Span> for SpanSet {
+    fn extend<T: IntoIterator<Item = Span>>(&mut self, iter: T) {
+        self.spans.extend(
+            iter.into_iter()
+                .filter(|span| span.len_chars().is_some_and(|len| len > 0)),
+        );
+        Span::coalesce(&mut self.spans);
+    }
+}
+
+/// Spans in a SpanSet are never unknown
+fn absolute(span: &Span) -> AbsoluteSpan {
+    span.absolute.unwrap_or(AbsoluteSpan {
+        start: 0,
+        end: 0,
+        start_byte: 0,
+        end_byte: 0,
+    })
+}
+
+/// One end of a span, new spans are built by pairing up the ends of existing
+/// ones so their line and column are always known
+#[derive(Clone, Copy)]
+struct Boundary {
+    loc: usize,
+    byte: usize,
+    position: LineAndColumn,
+}
+
+impl Boundary {
+    fn start(span: &Span) -> Self {
+        let absolute = absolute(span);
+        Boundary {
+            loc: absolute.start,
+            byte: absolute.start_byte,
+            position: span.relative.start,
+        }
+    }
+
+    fn end(span: &Span) -> Self {
+        let absolute = absolute(span);
+        Boundary {
+            loc: absolute.end,
+            byte: absolute.end_byte,
+            position: span.relative.end,
+        }
+    }
+
+    fn max(self, other: Boundary) -> Boundary {
+        if other.byte > self.byte { other } else { self }
+    }
+
+    fn min(self, other: Boundary) -> Boundary {
+        if other.byte < self.byte { other } else { self }
+    }
+
+    fn span(start: Boundary, end: Boundary) -> Span {
+        Span {
+            absolute: Some(AbsoluteSpan {
+                start: start.loc,
+                end: end.loc,
+                start_byte: start.byte,
+                end_byte: end.byte,
+            }),
+            relative: RelativeSpan {
+                start: start.position,
+                end: end.position,
+            },
+            provenance: Provenance::NONE,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::testing::span_of;
+
+    const SOURCE: &str = "0123456789";
+
+    fn set(ranges: &[(usize, usize)]) -> SpanSet {
+        ranges
+            .iter()
+            .map(|&(start, end)| Span::from_byte_range(SOURCE, start, end))
+            .collect()
+    }
+
+    #[test]
+    fn merges_touching_spans() {
+        let set = set(&[(5, 7), (0, 2), (2, 4), (6, 8)]);
+        assert_eq!(set, self::set(&[(0, 4), (5, 8)]));
+        assert_eq!(set.iter().next(), Some(span_of(SOURCE, "0123", 0)));
+    }
+
+    #[test]
+    fn ignores_empty_and_unknown_spans() {
+        let set = [Span::UNKNOWN, Span::from_byte_range(SOURCE, 3, 3)]
+            .into_iter()
+            .collect::<SpanSet>();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn contains() {
+        let set = set(&[(1, 3), (5, 6)]);
+        let covered = (0..8).filter(|&i| set.contains(i)).collect::<Vec<_>>();
+        assert_eq!(covered, [1, 2, 5]);
+    }
+
+    #[test]
+    fn intersection() {
+        let left = set(&[(0, 4), (6, 9)]);
+        let right = set(&[(2, 7), (8, 10)]);
+        assert_eq!(left.intersection(&right), set(&[(2, 4), (6, 7), (8, 9)]));
+    }
+
+    #[test]
+    fn gaps() {
+        let set = set(&[(0, 2), (4, 6)]);
+        assert_eq!(
+            set.gaps(SOURCE).collect::<SpanSet>(),
+            self::set(&[(2, 4), (6, 10)])
+        );
+        assert!((set.coverage(SOURCE.len()) - 0.4).abs() < f64::EPSILON);
+        assert_eq!(SpanSet::new().gaps(SOURCE).count(), 1);
+        assert_eq!(self::set(&[(0, 10)]).gaps(SOURCE).count(), 0);
+    }
+
+    #[test]
+    fn difference() {
+        let left = set(&[(0, 4), (6, 10)]);
+        let right = set(&[(1, 2), (3, 7), (9, 10)]);
+        assert_eq!(left.difference(&right), set(&[(0, 1), (2, 3), (7, 9)]));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::Span;
+
+/// Static interval tree mapping spans to payloads, for answering "what covers
+/// this offset" over many spans (e.g. every token in a file) without a linear
+/// scan.
+///
+/// Built once from a collection of spans, queries take O(log n + k) for k
+/// results. Span::UNKNOWN entries can never match a query so they are
+/// dropped.
+///
+/// ```
+/// # use span::*;
+/// let source = "fn main() { x }";
+/// let tree = [
+///     (testing::span_of(source, source, 0), "function"),
+///     (testing::span_of(source, "main", 0), "name"),
+///     (testing::span_of(source, "{ x }", 0), "body"),
+///     (testing::span_of(source, "x", 0), "expression"),
+/// ]
+/// .into_iter()
+/// .collect::<SpanTree<_>>();
+///
+/// let names = |found: Vec<(Span, &&'static str)>| {
+///     found.into_iter().map(|(_, name)| *name).collect::<Vec<_>>()
+/// };
+/// assert_eq!(names(tree.covering(12)), ["function", "body", "expression"]);
+/// assert_eq!(names(tree.covering(3)), ["function", "name"]);
+/// assert_eq!(
+///     names(tree.overlapping(testing::span_of(source, "in() {", 0))),
+///     ["function", "name", "body"]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpanTree<T> {
+    /// Sorted by start, the tree is implicit with the root of each range at
+    /// its midpoint
+    entries: Vec<(Span, T)>,
+    /// Largest end byte in the subtree rooted at each index
+    max_end: Vec<usize>,
+}
+
+impl<T> SpanTree<T> {
+    /// Build the tree
+    pub fn new(entries: impl IntoIterator<Item = (Span, T)>) -> Self {
+        let mut entries = entries
+            .into_iter()
+            .filter(|(span, _)| !span.is_unknown())
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(span, _)| (start(span), end(span)));
+        let mut max_end = vec![0; entries.len()];
+        let _ = build(&entries, &mut max_end, 0, entries.len());
+        Self { entries, max_end }
+    }
+
+    /// Number of spans in the tree
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the tree contains no spans
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over the entries ordered by start
+    pub fn iter(&self) -> impl Iterator<Item = (Span, &T)> + '_ {
+        self.entries.iter().map(|(span, value)| (*span, value))
+    }
+
+    /// All entries whose span covers the byte at `offset`, ordered by start
+    #[must_use]
+    pub fn covering(&self, offset: usize) -> Vec<(Span, &T)> {
+        self.query(offset, offset + 1)
+    }
+
+    /// All entries whose span overlaps `span`, ordered by start. An empty
+    /// span overlaps the entries covering its start
+    #[must_use]
+    pub fn overlapping(&self, span: Span) -> Vec<(Span, &T)> {
+        if span.is_unknown() {
+            return Vec::new();
+        }
+        self.query(start(&span), end(&span).max(start(&span) + 1))
+    }
+
+    /// Entries that overlap the byte range `from..to`
+    fn query(&self, from: usize, to: usize) -> Vec<(Span, &T)> {
+        let mut found = Vec::new();
+        self.search(from, to, 0, self.entries.len(), &mut found);
+        found
+    }
+
+    fn search<'a>(
+        &'a self,
+        from: usize,
+        to: usize,
+        lo: usize,
+        hi: usize,
+        found: &mut Vec<(Span, &'a T)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        if self.max_end[mid] <= from {
+            return;
+        }
+        self.search(from, to, lo, mid, found);
+        let (span, value) = &self.entries[mid];
+        if start(span) >= to {
+            return;
+        }
+        if end(span) > from {
+            found.push((*span, value));
+        }
+        self.search(from, to, mid + 1, hi, found);
+    }
+}
+
+impl<T> FromIterator<(Span, T)> for SpanTree<T> {
+    fn from_iter<I: IntoIterator<Item = (Span, T)>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+/// Fill in `max_end` for the subtree covering `lo..hi`, returning its max
+fn build<T>(
+    entries: &[(Span, T)],
+    max_end: &mut [usize],
+    lo: usize,
+    hi: usize,
+) -> usize {
+    if lo >= hi {
+        return 0;
+    }
+    let mid = lo + (hi - lo) / 2;
+    let left = build(entries, max_end, lo, mid);
+    let right = build(entries, max_end, mid + 1, hi);
+    max_end[mid] = end(&entries[mid].0).max(left).max(right);
+    max_end[mid]
+}
+
+fn start(span: &Span) -> usize {
+    span.absolute.map_or(0, |absolute| absolute.start_byte)
+}
+
+fn end(span: &Span) -> usize {
+    span.absolute.map_or(0, |absolute| absolute.end_byte)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn matches_linear_scan() {
+        let source = "x".repeat(64);
+        let ranges = (0..40)
+            .map(|i| ((i * 7) % 50, (i * 7) % 50 + (i % 9)))
+            .collect::<Vec<_>>();
+        let tree = ranges
+            .iter()
+            .map(|&(start, end)| {
+                (Span::from_byte_range(&source, start, end), (start, end))
+            })
+            .collect::<SpanTree<_>>();
+        assert_eq!(tree.len(), ranges.len());
+        for offset in 0..64 {
+            let mut expected = ranges
+                .iter()
+                .filter(|&&(start, end)| start <= offset && offset < end)
+                .collect::<Vec<_>>();
+            expected.sort();
+            let mut actual = tree
+                .covering(offset)
+                .into_iter()
+                .map(|(_, range)| range)
+                .collect::<Vec<_>>();
+            actual.sort();
+            assert_eq!(expected, actual, "offset {offset}");
+        }
+    }
+
+    #[test]
+    fn unknown_spans_are_dropped() {
+        let tree = SpanTree::new([(Span::UNKNOWN, ())]);
+        assert!(tree.is_empty());
+        assert!(tree.overlapping(Span::UNKNOWN).is_empty());
+    }
+}
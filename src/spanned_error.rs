@@ -0,0 +1,141 @@
+use std::fmt;
+
+use crate::{LookaheadExceeded, MalformedNumber, NotAdjacent, Span, snippet};
+
+/// An error that points at the source it was produced from, so errors of
+/// different types can be rendered with the same code
+///
+/// By the convention followed by the errors in this crate the error's
+/// Display starts with its span, e.g. `line 1 column 3: expected digits`
+///
+/// ```
+/// # use span::*;
+/// #[derive(Debug)]
+/// struct Unknown(Span, Span);
+///
+/// impl std::fmt::Display for Unknown {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "{}: unknown variable", self.0)
+///     }
+/// }
+///
+/// impl std::error::Error for Unknown {}
+///
+/// impl SpannedError for Unknown {
+///     fn span(&self) -> Span {
+///         self.0
+///     }
+///
+///     fn labels(&self) -> Vec<(Span, String)> {
+///         vec![(self.1, String::from("did you mean this?"))]
+///     }
+/// }
+///
+/// const SRC: &str = "let xs = 1;\nx";
+/// let error = Unknown(
+///     testing::span_of(SRC, "x", 1),
+///     testing::span_of(SRC, "xs", 0),
+/// );
+/// assert_eq!(
+///     error.in_source(SRC).to_string(),
+///     "line 2 column 1: unknown variable\n\
+///      2 | x\n  \
+///        | ^\n\
+///      line 1 column 5 to column 7: did you mean this?\n\
+///      1 | let xs = 1;\n  \
+///        |     ^^\n"
+/// );
+/// ```
+pub trait SpannedError: std::error::Error {
+    /// The source the error is about
+    fn span(&self) -> Span;
+
+    /// Other relevant parts of the source, each with a message explaining
+    /// why it is relevant. Empty by default
+    fn labels(&self) -> Vec<(Span, String)> {
+        Vec::new()
+    }
+
+    /// Display the error followed by the source lines its span covers with
+    /// the span underlined, then each label and its source lines. `source`
+    /// must be the text the spans point into
+    fn in_source<'a>(&'a self, source: &'a str) -> InSource<'a, Self>
+    where
+        Self: Sized,
+    {
+        InSource::new(self, source)
+    }
+}
+
+/// See [SpannedError::in_source]
+#[derive(Debug, Clone, Copy)]
+pub struct InSource<'a, E: ?Sized> {
+    error: &'a E,
+    source: &'a str,
+}
+
+impl<'a, E: SpannedError + ?Sized> InSource<'a, E> {
+    /// Same as [SpannedError::in_source], also usable with a
+    /// `dyn SpannedError`
+    #[must_use]
+    pub fn new(error: &'a E, source: &'a str) -> Self {
+        Self { error, source }
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl<E: SpannedError + ?Sized> fmt::Display for InSource<'_, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.error)?;
+        snippet::write_snippet(f, self.source, self.error.span())?;
+        for (span, message) in self.error.labels() {
+            writeln!(f, "{span:#}: {message}")?;
+            snippet::write_snippet(f, self.source, span)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl SpannedError for LookaheadExceeded {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl SpannedError for MalformedNumber {
+    fn span(&self) -> Span {
+        match self {
+            MalformedNumber::MissingDigits(span)
+            | MalformedNumber::InvalidDigit(_, span) => *span,
+        }
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl SpannedError for NotAdjacent {
+    fn span(&self) -> Span {
+        self.gap
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::Chars;
+
+    #[test]
+    fn render_dyn_error() {
+        const SRC: &str = "0x";
+        let error = Chars::new(SRC).scan_integer().unwrap().unwrap_err();
+        let error: Box<dyn SpannedError> = Box::new(error);
+        assert_eq!(
+            InSource::new(&*error, SRC).to_string(),
+            "line 1 column 1: expected digits\n1 | 0x\n  | ^^\n"
+        );
+    }
+}
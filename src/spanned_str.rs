@@ -0,0 +1,233 @@
+use std::{fmt, ops::Deref, ops::Range};
+
+use crate::{AbsoluteSpan, LineAndColumn, RelativeSpan, Span};
+
+/// A slice of source text along with the span it came from. Derefs to `str`,
+/// and slicing, splitting and trimming narrow the span to match so pieces of
+/// a token can be reported precisely.
+///
+/// Like [Span::lines] the positions of narrowed spans are worked out from
+/// the text, so they assume the default
+/// [ColumnPolicy::Chars](crate::ColumnPolicy::Chars).
+///
+/// ```
+/// # use span::*;
+/// const SRC: &str = "key = \"  a, b \"";
+/// let value = SpannedStr::new(SRC, testing::span_of(SRC, "  a, b ", 0));
+/// let value = value.unwrap();
+/// assert_eq!(value.len(), 7);
+/// let items = value.split(",").map(|s| s.trim()).collect::<Vec<_>>();
+/// let text = items.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+/// assert_eq!(text, ["a", "b"]);
+/// assert_eq!(items[1].span(), testing::span_of(SRC, "b", 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpannedStr<'a> {
+    text: &'a str,
+    span: Span,
+}
+
+impl<'a> SpannedStr<'a> {
+    /// The text `span` covers in `source`. Returns None for Span::UNKNOWN
+    /// and spans that don't fit `source`
+    #[must_use]
+    pub fn new(source: &'a str, span: Span) -> Option<Self> {
+        let absolute = span.absolute?;
+        let text = source.get(absolute.start_byte..absolute.end_byte)?;
+        Some(Self { text, span })
+    }
+
+    /// The text
+    #[must_use]
+    pub fn as_str(&self) -> &'a str {
+        self.text
+    }
+
+    /// Where the text came from
+    #[must_use]
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The part of the text in the byte range `range`, None if the range is
+    /// out of bounds or doesn't fall on character boundaries
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "x = 0xff;";
+    /// let literal = testing::span_of(SRC, "0xff", 0);
+    /// let literal = SpannedStr::new(SRC, literal).unwrap();
+    /// let digits = literal.slice(2..4).unwrap();
+    /// assert_eq!(digits.span(), testing::span_of(SRC, "ff", 0));
+    /// assert_eq!(literal.slice(2..5), None);
+    /// ```
+    #[must_use]
+    pub fn slice(&self, range: Range<usize>) -> Option<Self> {
+        let _ = self.text.get(range.clone())?;
+        Some(self.sub(range.start, range.end))
+    }
+
+    /// Split the text at byte offset `mid`, None if `mid` is out of bounds or
+    /// not on a character boundary
+    #[must_use]
+    pub fn split_at(&self, mid: usize) -> Option<(Self, Self)> {
+        let _ = self.text.get(..mid)?;
+        Some((self.sub(0, mid), self.sub(mid, self.text.len())))
+    }
+
+    /// Split the text around the first occurrence of `separator`
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "name=value";
+    /// let pair = SpannedStr::new(SRC, testing::span_of(SRC, SRC, 0));
+    /// let (name, value) = pair.unwrap().split_once("=").unwrap();
+    /// let span = value.span();
+    /// assert_eq!(format!("{span:#}"), "line 1 column 6 to column 11");
+    /// assert_eq!(&*name, "name");
+    /// ```
+    #[must_use]
+    pub fn split_once(&self, separator: &str) -> Option<(Self, Self)> {
+        let start = self.text.find(separator)?;
+        Some((
+            self.sub(0, start),
+            self.sub(start + separator.len(), self.text.len()),
+        ))
+    }
+
+    /// The pieces of text between occurrences of `separator`, as
+    /// [str::split]
+    pub fn split<'b>(
+        &self,
+        separator: &'b str,
+    ) -> impl Iterator<Item = SpannedStr<'a>> + 'b
+    where
+        'a: 'b,
+    {
+        let this = *self;
+        let mut start = 0;
+        self.text
+            .match_indices(separator)
+            .map(|(i, separator)| (i, i + separator.len()))
+            .chain([(self.text.len(), self.text.len())])
+            .map(move |(end, next)| {
+                let piece = this.sub(start, end);
+                start = next;
+                piece
+            })
+    }
+
+    /// The text without leading and trailing whitespace
+    #[must_use]
+    pub fn trim(&self) -> Self {
+        self.trim_start().trim_end()
+    }
+
+    /// The text without leading whitespace
+    #[must_use]
+    pub fn trim_start(&self) -> Self {
+        let start = self.text.len() - self.text.trim_start().len();
+        self.sub(start, self.text.len())
+    }
+
+    /// The text without trailing whitespace
+    #[must_use]
+    pub fn trim_end(&self) -> Self {
+        self.sub(0, self.text.trim_end().len())
+    }
+
+    /// The text between byte offsets `start` and `end`, which must be
+    /// character boundaries
+    fn sub(&self, start: usize, end: usize) -> Self {
+        let Some(absolute) = self.span.absolute else {
+            return *self;
+        };
+        let (start_loc, start_position) = advance(
+            absolute.start,
+            self.span.relative.start,
+            &self.text[..start],
+        );
+        let (end_loc, end_position) =
+            advance(start_loc, start_position, &self.text[start..end]);
+        let mut span = self.span;
+        span.absolute = Some(AbsoluteSpan {
+            start: start_loc,
+            end: end_loc,
+            start_byte: absolute.start_byte + start,
+            end_byte: absolute.start_byte + end,
+        });
+        span.relative = RelativeSpan {
+            start: start_position,
+            end: end_position,
+        };
+        Self {
+            text: &self.text[start..end],
+            span,
+        }
+    }
+}
+
+/// Position after `text` if it starts at `loc` and `position`
+fn advance(
+    loc: usize,
+    position: LineAndColumn,
+    text: &str,
+) -> (usize, LineAndColumn) {
+    let loc = loc + text.chars().count();
+    let position = match text.rsplit_once('\n') {
+        Some((before, last)) => LineAndColumn {
+            line: position.line + before.matches('\n').count() + 1,
+            column: last.chars().count() + 1,
+        },
+        None => LineAndColumn {
+            line: position.line,
+            column: position.column + text.chars().count(),
+        },
+    };
+    (loc, position)
+}
+
+impl Deref for SpannedStr<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.text
+    }
+}
+
+#[cfg_attr(coverage, coverage(off))]
+impl fmt::Display for SpannedStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::testing::span_of;
+
+    #[test]
+    fn narrowed_spans_match_source_spans() {
+        const SRC: &str = "a\n  £b\n\nc  ";
+        let all = SpannedStr::new(SRC, span_of(SRC, SRC, 0)).unwrap();
+        let pieces = all.split("\n").map(|s| s.trim()).collect::<Vec<_>>();
+        let expected = [
+            span_of(SRC, "a", 0),
+            span_of(SRC, "£b", 0),
+            Span::from_byte_range(SRC, 8, 8),
+            span_of(SRC, "c", 0),
+        ];
+        assert_eq!(
+            pieces.iter().map(SpannedStr::span).collect::<Vec<_>>(),
+            expected
+        );
+        let (before, after) = all.split_at(4).unwrap();
+        assert_eq!(before.span(), span_of(SRC, "a\n  ", 0));
+        assert_eq!(after.span(), Span::from_byte_range(SRC, 4, SRC.len()));
+        assert_eq!(all.split_at(5), None);
+    }
+}
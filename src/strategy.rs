@@ -0,0 +1,65 @@
+//! proptest strategies for spans and the text [Chars](crate::Chars) runs
+//! over. Spans are made the same way as with the `arbitrary` feature, by
+//! running Chars over generated source text, so they are always internally
+//! consistent
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use span::{SourceAndSpan, strategy};
+//!
+//! proptest!(|(SourceAndSpan { source, span } in strategy::source_and_span())| {
+//!     let end = span.start().unwrap() + span.len_chars().unwrap();
+//!     prop_assert!(end <= source.chars().count());
+//! });
+//! ```
+
+use proptest::{collection::vec, prelude::*};
+
+use crate::{SourceAndSpan, Span};
+
+/// Source text with plenty of the characters that affect positions: line
+/// terminators, tabs, multi-byte and wide characters
+pub fn source() -> impl Strategy<Value = String> {
+    let c = prop_oneof![
+        4 => any::<char>(),
+        2 => Just('\n'),
+        1 => Just('\r'),
+        1 => Just('\t'),
+        1 => Just('£'),
+        1 => Just('名'),
+    ];
+    vec(c, 0..64).prop_map(String::from_iter)
+}
+
+prop_compose! {
+    /// [source] paired with a span covering a range of it
+    pub fn source_and_span()(source in source())(
+        start in 0..=source.chars().count(),
+        len in 0..=source.chars().count(),
+        source in Just(source),
+    ) -> SourceAndSpan {
+        let end = (start + len).min(source.chars().count());
+        SourceAndSpan::new(source, start, end)
+    }
+}
+
+/// Spans from [source_and_span], occasionally [Span::UNKNOWN]
+pub fn span() -> impl Strategy<Value = Span> {
+    prop_oneof![
+        1 => Just(Span::UNKNOWN),
+        15 => source_and_span().prop_map(|generated| generated.span),
+    ]
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn spans_are_valid(span in span()) {
+            prop_assert_eq!(span.validate(), Ok(()));
+        }
+    }
+}
@@ -0,0 +1,393 @@
+//! Helpers for testing code that produces spans
+
+use std::{
+    fmt::{Debug, Display, Write as _},
+    path::Path,
+};
+
+use crate::{AbsoluteSpan, Span, snippet::write_snippet};
+
+/// Assert that two spans are equal. Unlike `assert_eq!` failures show both
+/// spans by line and column along with a list of the fields that differ.
+///
+/// The comparison is strict, [Span::UNKNOWN] is only equal to itself.
+///
+/// ```
+/// # use span::*;
+/// let mut chars = Chars::new("123");
+/// let start = chars.start_token();
+/// let _ = chars.next();
+/// let span = chars.end_token(start);
+/// assert_span_eq!(span, span);
+/// assert_span_eq!(Span::UNKNOWN, Span::UNKNOWN, "with a {}", "message");
+/// ```
+/// ```should_panic
+/// # use span::*;
+/// let mut chars = Chars::new("123");
+/// let start = chars.start_token();
+/// let _ = chars.next();
+/// let span = chars.end_token(start);
+/// assert_span_eq!(span, Span::UNKNOWN);
+/// ```
+#[macro_export]
+macro_rules! assert_span_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        if let Some(diff) = $crate::testing::span_diff(&$left, &$right) {
+            panic!("assertion `left == right` failed\n{diff}");
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        if let Some(diff) = $crate::testing::span_diff(&$left, &$right) {
+            panic!(
+                "assertion `left == right` failed: {}\n{diff}",
+                format_args!($($arg)+)
+            );
+        }
+    };
+}
+
+/// Describe the differences between two spans, returns None if they are
+/// equal. Used by [assert_span_eq]
+///
+/// ```
+/// # use span::*;
+/// let mut chars = Chars::new("123");
+/// let start1 = chars.start_token();
+/// let start2 = chars.start_token();
+/// let _ = chars.next();
+/// let span1 = chars.end_token(start1);
+/// let _ = chars.next();
+/// let span2 = chars.end_token(start2);
+/// assert_eq!(testing::span_diff(&span1, &span1), None);
+/// let diff = testing::span_diff(&span1, &span2).unwrap();
+/// assert!(diff.contains("right: line 1 column 1 to column 3 (bytes 0..2)"));
+/// assert!(diff.contains("end_column: 2 != 3"));
+/// assert!(!diff.contains("start_column"));
+/// ```
+#[must_use]
+pub fn span_diff(left: &Span, right: &Span) -> Option<String> {
+    if left == right {
+        return None;
+    }
+    let mut diff = String::new();
+    let _ = writeln!(diff, "  left: {}", describe(left));
+    let _ = writeln!(diff, " right: {}", describe(right));
+    for ((name, left), (_, right)) in
+        fields(left).into_iter().zip(fields(right))
+    {
+        if left != right {
+            let _ =
+                writeln!(diff, "   {name}: {} != {}", show(left), show(right));
+        }
+    }
+    Some(diff)
+}
+
+/// Assert that two sequences of spanned items (e.g. a lexer's tokens) are
+/// equal. Failures report the first item that differs, see
+/// [testing::sequence_diff](crate::testing::sequence_diff)
+///
+/// ```should_panic
+/// # use span::*;
+/// const SRC: &str = "a b";
+/// let expected = [("a", testing::span_of(SRC, "a", 0))];
+/// let actual = [("a", testing::span_of(SRC, "a b", 0))];
+/// assert_spans_eq!(actual, expected);
+/// ```
+#[macro_export]
+macro_rules! assert_spans_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        if let Some(diff) = $crate::testing::sequence_diff(&$left, &$right) {
+            panic!("assertion `left == right` failed\n{diff}");
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        if let Some(diff) = $crate::testing::sequence_diff(&$left, &$right) {
+            panic!(
+                "assertion `left == right` failed: {}\n{diff}",
+                format_args!($($arg)+)
+            );
+        }
+    };
+}
+
+/// Describe the first difference between two sequences of spanned items,
+/// returns None if they are equal. Spans are shown by line and column
+/// rather than as raw fields. Used by [assert_spans_eq]
+///
+/// ```
+/// # use span::*;
+/// const SRC: &str = "let x";
+/// let token = |text, n| (text, testing::span_of(SRC, text, n));
+/// let expected = [token("let", 0), token("x", 0)];
+/// assert_eq!(testing::sequence_diff(&expected, &expected), None);
+///
+/// let actual = [token("let", 0), token("t", 0)];
+/// let diff = testing::sequence_diff(&actual, &expected).unwrap();
+/// assert_eq!(
+///     diff,
+///     "first difference at index 1\n  \
+///        left: \"t\" at line 1 column 3 (bytes 2..3)\n \
+///       right: \"x\" at line 1 column 5 (bytes 4..5)\n"
+/// );
+///
+/// let diff = testing::sequence_diff(&expected[..1], &expected).unwrap();
+/// assert!(diff.contains("left: <missing>"));
+/// ```
+#[must_use]
+pub fn sequence_diff<T: Debug + PartialEq>(
+    left: &[(T, Span)],
+    right: &[(T, Span)],
+) -> Option<String> {
+    let index = (0..left.len().max(right.len()))
+        .find(|&i| left.get(i) != right.get(i))?;
+    let item = |item: Option<&(T, Span)>| match item {
+        Some((value, span)) => format!("{value:?} at {}", describe(span)),
+        None => String::from("<missing>"),
+    };
+    let mut diff = format!("first difference at index {index}\n");
+    let _ = writeln!(diff, "  left: {}", item(left.get(index)));
+    let _ = writeln!(diff, " right: {}", item(right.get(index)));
+    Some(diff)
+}
+
+/// Environment variable that makes [assert_golden] overwrite expected files
+/// instead of comparing against them
+pub const UPDATE_GOLDEN: &str = "SPAN_UPDATE_GOLDEN";
+
+/// Render diagnostics as plain text for snapshot testing with
+/// [assert_golden]. Each diagnostic is rendered as its span, its message and
+/// the source lines it covers with the span underlined. Diagnostics are
+/// ordered by position then message, so the output doesn't depend on the
+/// order they were produced in.
+///
+/// ```
+/// # use span::*;
+/// const SRC: &str = "let x = y;\nlet z = x;";
+/// let rendered = testing::render_diagnostics(SRC, &[
+///     (testing::span_of(SRC, "z", 0), "unused variable"),
+///     (testing::span_of(SRC, "y", 0), "unknown variable"),
+///     (Span::UNKNOWN, "somewhere"),
+/// ]);
+/// assert_eq!(
+///     rendered,
+///     "???: somewhere\n\
+///      line 1 column 9: unknown variable\n\
+///      1 | let x = y;\n  \
+///          |         ^\n\
+///      line 2 column 5: unused variable\n\
+///      2 | let z = x;\n  \
+///          |     ^\n"
+/// );
+/// ```
+#[must_use]
+pub fn render_diagnostics(
+    source: &str,
+    diagnostics: &[(Span, impl Display)],
+) -> String {
+    let mut diagnostics = diagnostics
+        .iter()
+        .map(|(span, message)| (*span, message.to_string()))
+        .collect::<Vec<_>>();
+    diagnostics.sort_by(|(a, a_message), (b, b_message)| {
+        let key = |span: &Span| span.absolute.map(|a| (a.start, a.end));
+        key(a).cmp(&key(b)).then_with(|| a_message.cmp(b_message))
+    });
+    let mut rendered = String::new();
+    for (span, message) in diagnostics {
+        let _ = writeln!(rendered, "{span:#}: {message}");
+        let _ = write_snippet(&mut rendered, source, span);
+    }
+    rendered
+}
+
+/// Compare `actual` against the contents of the file at `path`, e.g. the
+/// output of [render_diagnostics] against a checked-in `.expected` file.
+///
+/// If the [UPDATE_GOLDEN] environment variable is set to anything other than
+/// an empty string the file is overwritten with `actual` instead, so
+/// expected files can be regenerated with e.g.
+/// `SPAN_UPDATE_GOLDEN=1 cargo test`
+///
+/// ```no_run
+/// # use span::*;
+/// const SRC: &str = "let x = y;";
+/// let rendered = testing::render_diagnostics(SRC, &[
+///     (testing::span_of(SRC, "y", 0), "unknown variable"),
+/// ]);
+/// testing::assert_golden("tests/golden/unknown.expected", &rendered);
+/// ```
+///
+/// # Panics
+/// If the contents of the file differ from `actual`, showing a line by line
+/// diff, or if the file can't be read or written
+pub fn assert_golden(path: impl AsRef<Path>, actual: &str) {
+    let update = std::env::var_os(UPDATE_GOLDEN)
+        .is_some_and(|update| !update.is_empty());
+    assert_golden_with(path.as_ref(), actual, update);
+}
+
+/// [assert_golden] with the decision to update the file made by the caller
+fn assert_golden_with(path: &Path, actual: &str, update: bool) {
+    if update {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap_or_else(|e| {
+                panic!("failed to create {}: {e}", parent.display())
+            });
+        }
+        std::fs::write(path, actual).unwrap_or_else(|e| {
+            panic!("failed to write {}: {e}", path.display())
+        });
+        return;
+    }
+    let expected = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        panic!(
+            "failed to read {}: {e}\nrun with {UPDATE_GOLDEN}=1 to create it",
+            path.display()
+        )
+    });
+    assert!(
+        expected == actual,
+        "{} doesn't match (- expected, + actual)\n{}\
+         run with {UPDATE_GOLDEN}=1 to update it",
+        path.display(),
+        line_diff(&expected, actual)
+    );
+}
+
+/// Line by line diff of two texts, based on their longest common subsequence
+/// of lines
+fn line_diff(expected: &str, actual: &str) -> String {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+    // common[i][j] is the length of the longest common subsequence of
+    // expected[i..] and actual[j..]
+    let mut common = vec![vec![0; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut diff = String::new();
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            let _ = writeln!(diff, "  {}", expected[i]);
+            i += 1;
+            j += 1;
+        } else if j == actual.len()
+            || (i < expected.len() && common[i + 1][j] >= common[i][j + 1])
+        {
+            let _ = writeln!(diff, "- {}", expected[i]);
+            i += 1;
+        } else {
+            let _ = writeln!(diff, "+ {}", actual[j]);
+            j += 1;
+        }
+    }
+    diff
+}
+
+fn describe(span: &Span) -> String {
+    if span.is_unknown() {
+        return format!("{span}");
+    }
+    format!("{span:#} ({})", span.display_bytes())
+}
+
+fn show(field: Option<usize>) -> String {
+    field.map_or_else(|| String::from("???"), |field| field.to_string())
+}
+
+fn fields(span: &Span) -> [(&'static str, Option<usize>); 8] {
+    let absolute = span.absolute;
+    let get = |f: fn(AbsoluteSpan) -> usize| absolute.map(f);
+    let relative = |value: usize| absolute.map(|_| value);
+    [
+        ("start", get(|a| a.start)),
+        ("end", get(|a| a.end)),
+        ("start_byte", get(|a| a.start_byte)),
+        ("end_byte", get(|a| a.end_byte)),
+        ("start_line", relative(span.relative.start.line)),
+        ("start_column", relative(span.relative.start.column)),
+        ("end_line", relative(span.relative.end.line)),
+        ("end_column", relative(span.relative.end.column)),
+    ]
+}
+
+/// Span of the nth (0 indexed) occurrence of `needle` in `source`, so
+/// expected spans don't have to be worked out by hand
+///
+/// ```
+/// # use span::*;
+/// const SRC: &str = "let x = 1;\nlet y = 2;";
+/// let mut chars = Chars::new(SRC);
+/// for _ in (&mut chars).take(11) {}
+/// let start = chars.start_token();
+/// for _ in (&mut chars).take(3) {}
+/// let span = chars.end_token(start);
+/// assert_span_eq!(span, testing::span_of(SRC, "let", 1));
+/// ```
+///
+/// # Panics
+/// If `source` contains fewer than `occurrence + 1` copies of `needle`
+#[must_use]
+pub fn span_of(source: &str, needle: &str, occurrence: usize) -> Span {
+    let Some((start, _)) = source.match_indices(needle).nth(occurrence) else {
+        panic!("{needle:?} occurs fewer than {} times", occurrence + 1);
+    };
+    Span::from_byte_range(source, start, start + needle.len())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn multi_line_diagnostic() {
+        const SRC: &str = "fn f() {\n\tg(\n\t1)\n}";
+        let rendered = render_diagnostics(
+            SRC,
+            &[(span_of(SRC, "g(\n\t1)", 0), "bad call")],
+        );
+        assert_eq!(
+            rendered,
+            "line 2 column 2 to line 3 column 4: bad call\n\
+             2 | \tg(\n  \
+               | \t^^\n\
+             3 | \t1)\n  \
+               | ^^^\n"
+        );
+    }
+
+    #[test]
+    fn line_diff_marks_changes() {
+        assert_eq!(
+            line_diff("a\nb\nc\n", "a\nx\nc\nd\n"),
+            "  a\n- b\n+ x\n  c\n+ d\n"
+        );
+    }
+
+    #[test]
+    fn golden_file_matches() {
+        let path = std::env::temp_dir()
+            .join(format!("span-golden-{}.expected", std::process::id()));
+        std::fs::write(&path, "expected\n").unwrap();
+        assert_golden_with(&path, "expected\n", false);
+        let result = std::panic::catch_unwind(|| {
+            assert_golden_with(&path, "actual\n", false);
+        });
+        assert!(result.is_err());
+        assert_golden_with(&path, "actual\n", true);
+        let updated = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(updated, "actual\n");
+    }
+}
@@ -0,0 +1,158 @@
+use std::ops::Range;
+
+use crate::Span;
+
+/// The tokens that changed when a document was lexed again after an edit,
+/// see [TokenDiff::between]. Tokens outside the ranges are unchanged apart
+/// from being moved by the edit
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenDiff {
+    /// Indices of the old tokens that were removed
+    pub removed: Range<usize>,
+    /// Indices of the new tokens that replaced them
+    pub added: Range<usize>,
+    /// Region of the old text covered by the removed tokens, None if no
+    /// tokens were removed
+    pub removed_span: Option<Span>,
+    /// Region of the new text covered by the added tokens, None if no tokens
+    /// were added. This is what needs repainting
+    pub added_span: Option<Span>,
+}
+
+impl TokenDiff {
+    /// Compare the tokens from lexing a document before and after an edit
+    /// that replaced `edit` (a span of the old text) with `inserted` bytes of
+    /// new text. Tokens are `(token, span)` pairs as produced by e.g.
+    /// [Chars::scan_identifier](crate::Chars::scan_identifier), they are
+    /// unchanged if the token is equal and its span is the same or, after the
+    /// edit, moved by exactly the size of the edit.
+    ///
+    /// The edit bounds the search, tokens before it are compared from the
+    /// start and tokens after it from the end, so only the tokens around the
+    /// edit are reported even if the edit changes what they are
+    ///
+    /// ```
+    /// # use span::*;
+    /// let lex = |source: &str| {
+    ///     let mut chars = Chars::new(source);
+    ///     let mut tokens = Vec::new();
+    ///     loop {
+    ///         let _ = chars.skip_whitespace();
+    ///         let word = |c: char| !c.is_whitespace();
+    ///         match chars.scan_identifier(word, word) {
+    ///             Some(token) => tokens.push(token),
+    ///             None => return tokens,
+    ///         }
+    ///     }
+    /// };
+    /// const OLD: &str = "let x = y\nz";
+    /// const NEW: &str = "let xs = y\nz";
+    /// let old = lex(OLD);
+    /// let new = lex(NEW);
+    /// let edit = testing::span_of(OLD, "x", 0);
+    /// let diff = TokenDiff::between(&old, &new, edit, "xs".len());
+    /// assert_eq!((diff.removed, diff.added), (1..2, 1..2));
+    /// assert_eq!(diff.added_span, Some(testing::span_of(NEW, "xs", 0)));
+    /// ```
+    #[must_use]
+    pub fn between<T: PartialEq>(
+        old: &[(T, Span)],
+        new: &[(T, Span)],
+        edit: Span,
+        inserted: usize,
+    ) -> TokenDiff {
+        let (edit_start, edit_end) = edit
+            .absolute
+            .map_or((0, usize::MAX), |a| (a.start_byte, a.end_byte));
+        let new_edit_end = edit_start.saturating_add(inserted);
+        let end_byte = |span: &Span| span.absolute.map_or(0, |a| a.end_byte);
+        let start_byte =
+            |span: &Span| span.absolute.map_or(usize::MAX, |a| a.start_byte);
+
+        let prefix = old
+            .iter()
+            .zip(new)
+            .take_while(|((old, old_span), (new, new_span))| {
+                old == new
+                    && old_span == new_span
+                    && end_byte(old_span) <= edit_start
+            })
+            .count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|((old, old_span), (new, new_span))| {
+                let (old_start, new_start) =
+                    (start_byte(old_span), start_byte(new_span));
+                old == new
+                    && old_start != usize::MAX
+                    && new_start != usize::MAX
+                    && old_start >= edit_end
+                    && new_start >= new_edit_end
+                    && old_start - edit_end == new_start - new_edit_end
+                    && old_span.len_bytes() == new_span.len_bytes()
+            })
+            .count();
+
+        let removed = prefix..old.len() - suffix;
+        let added = prefix..new.len() - suffix;
+        let span = |tokens: &[(T, Span)]| {
+            let spans =
+                tokens.iter().map(|(_, span)| *span).collect::<Vec<_>>();
+            (!spans.is_empty()).then(|| Span::aggregate(&spans))
+        };
+        TokenDiff {
+            removed_span: span(&old[removed.clone()]),
+            added_span: span(&new[added.clone()]),
+            removed,
+            added,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::Chars;
+
+    fn lex(source: &str) -> Vec<(String, Span)> {
+        let mut chars = Chars::new(source);
+        let mut tokens = Vec::new();
+        loop {
+            let _ = chars.skip_whitespace();
+            match chars
+                .scan_identifier(|c| !c.is_whitespace(), |c| !c.is_whitespace())
+            {
+                Some(token) => tokens.push(token),
+                None => return tokens,
+            }
+        }
+    }
+
+    #[rstest]
+    // Deleting a token
+    #[case("a b c", "a c", 2..4, 0, 1..2, 1..1)]
+    // Inserting a token on a new line moves the later tokens
+    #[case("a\nb", "a\nx\nb", 2..2, 2, 1..1, 1..2)]
+    // Joining two tokens
+    #[case("ab cd", "abcd", 2..3, 0, 0..2, 0..1)]
+    // An edit in the middle of a token
+    #[case("one two", "one tWo", 5..6, 1, 1..2, 1..2)]
+    fn changed_tokens(
+        #[case] old: &str,
+        #[case] new: &str,
+        #[case] edit: Range<usize>,
+        #[case] inserted: usize,
+        #[case] removed: Range<usize>,
+        #[case] added: Range<usize>,
+    ) {
+        let edit = Span::from_byte_range(old, edit.start, edit.end);
+        let diff = TokenDiff::between(&lex(old), &lex(new), edit, inserted);
+        assert_eq!((diff.removed, diff.added), (removed, added));
+    }
+}
@@ -0,0 +1,40 @@
+use crate::Span;
+
+impl Span {
+    /// Span of a `toml_edit` item parsed from `source`. Returns None if the
+    /// item wasn't parsed (e.g. it was inserted or the document has been
+    /// edited so spans were dropped) or its span doesn't fit `source`
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "[package]\nname = \"span\"\n";
+    /// let document = toml_edit::Document::parse(SRC).unwrap();
+    /// let span = Span::from_toml_item(SRC, &document["package"]["name"]);
+    /// assert_eq!(span, Some(testing::span_of(SRC, "\"span\"", 0)));
+    /// ```
+    #[must_use]
+    pub fn from_toml_item(
+        source: &str,
+        item: &toml_edit::Item,
+    ) -> Option<Span> {
+        Span::from_range(source, item.span()?)
+    }
+
+    /// Span of a `toml_edit` parse error in `source`, None if the error has
+    /// no span or its span doesn't fit `source`
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "a = 1\nb = ?\n";
+    /// let error = SRC.parse::<toml_edit::DocumentMut>().unwrap_err();
+    /// let span = Span::from_toml_error(SRC, &error).unwrap();
+    /// assert_eq!(format!("{span}"), "line 2 column 5");
+    /// ```
+    #[must_use]
+    pub fn from_toml_error(
+        source: &str,
+        error: &toml_edit::TomlError,
+    ) -> Option<Span> {
+        Span::from_range(source, error.span()?)
+    }
+}
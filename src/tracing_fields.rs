@@ -0,0 +1,128 @@
+use std::ops::Range;
+
+use tracing::field::{DebugValue, debug};
+
+use crate::Span;
+
+/// A [Span] split into values that can be recorded as structured `tracing`
+/// fields, see [Span::as_tracing_fields]. Each field is None, and so not
+/// recorded, for Span::UNKNOWN
+#[derive(Debug, Clone)]
+pub struct TracingFields {
+    /// Line the span starts on, conventionally recorded as `src.line`
+    pub line: Option<usize>,
+    /// Column the span starts at, conventionally recorded as `src.col`
+    pub col: Option<usize>,
+    /// Bytes covered by the span, recorded as e.g. `4..7`. Conventionally
+    /// recorded as `src.range`
+    pub range: Option<DebugValue<Range<usize>>>,
+}
+
+impl Span {
+    /// The span's position as values that can be attached to `tracing`
+    /// events and spans as structured fields, so log processors can query
+    /// them without parsing a formatted string
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "let x = y;";
+    /// let span = testing::span_of(SRC, "y", 0);
+    /// let src = span.as_tracing_fields();
+    /// assert_eq!(src.line, Some(1));
+    /// assert_eq!(src.col, Some(9));
+    /// tracing::warn!(
+    ///     src.line = src.line,
+    ///     src.col = src.col,
+    ///     src.range = src.range,
+    ///     "unknown variable"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn as_tracing_fields(&self) -> TracingFields {
+        TracingFields {
+            line: self.start_line(),
+            col: self.start_line_and_column().map(|start| start.column),
+            range: self
+                .absolute
+                .map(|absolute| debug(absolute.start_byte..absolute.end_byte)),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use std::{
+        fmt,
+        sync::{Arc, Mutex},
+    };
+
+    use pretty_assertions::assert_eq;
+    use tracing::{
+        Event, Metadata, Subscriber,
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+    };
+
+    use super::*;
+    use crate::testing::span_of;
+
+    /// Subscriber that records the fields of every event as strings
+    #[derive(Default)]
+    struct Fields(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((field.name().to_string(), format!("{value:?}")));
+        }
+    }
+
+    impl Subscriber for Fields {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut Fields(Arc::clone(&self.0)));
+        }
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    fn record(span: Span) -> Vec<(String, String)> {
+        let fields = Fields::default();
+        let recorded = Arc::clone(&fields.0);
+        tracing::subscriber::with_default(fields, || {
+            let src = span.as_tracing_fields();
+            tracing::info!(
+                src.line = src.line,
+                src.col = src.col,
+                src.range = src.range,
+            );
+        });
+        recorded.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn fields_are_structured() {
+        const SRC: &str = "a\n£bc";
+        let field =
+            |name: &str, value: &str| (String::from(name), String::from(value));
+        assert_eq!(
+            record(span_of(SRC, "bc", 0)),
+            [
+                field("src.line", "2"),
+                field("src.col", "2"),
+                field("src.range", "4..6"),
+            ]
+        );
+        assert_eq!(record(Span::UNKNOWN), []);
+    }
+}
@@ -0,0 +1,91 @@
+use crate::Span;
+
+impl Span {
+    /// Span of `source` between two `yaml-rust` markers, e.g. from a
+    /// `MarkedEventReceiver` or a `ScanError`. Returns None if either marker
+    /// is past the end of `source` or `end` is before `start`
+    ///
+    /// ```
+    /// # use span::*;
+    /// use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+    /// use yaml_rust::scanner::Marker;
+    ///
+    /// #[derive(Default)]
+    /// struct Scalars(Vec<Marker>);
+    ///
+    /// impl MarkedEventReceiver for Scalars {
+    ///     fn on_event(&mut self, event: Event, marker: Marker) {
+    ///         if let Event::Scalar(..) = event {
+    ///             self.0.push(marker);
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// const SRC: &str = "é: 1\n";
+    /// let mut scalars = Scalars::default();
+    /// Parser::new(SRC.chars()).load(&mut scalars, false).unwrap();
+    /// let span = Span::from_yaml_markers(SRC, &scalars.0[1], &scalars.0[1]);
+    /// assert_eq!(span.map(|span| span.to_string()).as_deref(), Some("line 1 column 4"));
+    /// ```
+    #[cfg(feature = "yaml-rust")]
+    #[must_use]
+    pub fn from_yaml_markers(
+        source: &str,
+        start: &yaml_rust::scanner::Marker,
+        end: &yaml_rust::scanner::Marker,
+    ) -> Option<Span> {
+        // Markers count characters rather than bytes
+        let mut offsets =
+            source.char_indices().map(|(i, _)| i).chain([source.len()]);
+        let start_byte = offsets.nth(start.index())?;
+        let end_byte = match end.index().checked_sub(start.index())? {
+            0 => start_byte,
+            n => offsets.nth(n - 1)?,
+        };
+        Some(Span::from_byte_range(source, start_byte, end_byte))
+    }
+
+    /// Span of the character `serde_yaml` reported `error` at, where
+    /// `source` is the text that was being parsed. Returns None for errors
+    /// without a location or if the location is outside `source`.
+    ///
+    /// ```
+    /// # use span::*;
+    /// const SRC: &str = "a: 1\nb: [2\n";
+    /// let error = serde_yaml::from_str::<serde_yaml::Value>(SRC).unwrap_err();
+    /// let span = Span::from_serde_yaml_error(SRC, &error).unwrap();
+    /// assert_eq!(format!("{span:#}"), "line 3 column 1");
+    /// ```
+    #[cfg(feature = "serde_yaml")]
+    #[must_use]
+    pub fn from_serde_yaml_error(
+        source: &str,
+        error: &serde_yaml::Error,
+    ) -> Option<Span> {
+        let location = error.location()?;
+        Span::from_line_col(source, location.line(), location.column())
+    }
+}
+
+#[cfg(all(test, feature = "yaml-rust"))]
+#[cfg_attr(coverage, coverage(off))]
+mod test {
+    use pretty_assertions::assert_eq;
+    use yaml_rust::{YamlLoader, scanner::ScanError};
+
+    use super::*;
+
+    #[test]
+    fn markers_past_the_end() {
+        const SRC: &str = "a: [1, 2\n";
+        let error: ScanError = YamlLoader::load_from_str(SRC).unwrap_err();
+        let span = Span::from_yaml_markers(SRC, error.marker(), error.marker());
+        assert_eq!(span.and_then(|span| span.start_line()), Some(2));
+        let long: ScanError =
+            YamlLoader::load_from_str("a: [1, 2, 3\n").unwrap_err();
+        assert_eq!(
+            Span::from_yaml_markers(SRC, long.marker(), long.marker()),
+            None
+        );
+    }
+}